@@ -81,6 +81,48 @@ mod parse {
     }
 }
 
+mod parse_nested {
+    use super::*;
+
+    #[bench]
+    fn deeply_nested_values(bencher: &mut Bencher) {
+        let raw = format!(
+            "[NESTED]\narray = {}1{}\ndictionary = {}\n",
+            "[".repeat(100),
+            "]".repeat(100),
+            "{ key = ".repeat(100) + "1" + &" }".repeat(100),
+        );
+
+        bencher.iter(|| {
+            let result = raw.parse::<Ion>();
+            black_box(result.unwrap())
+        })
+    }
+}
+
+#[cfg(feature = "binary")]
+mod reload {
+    use super::*;
+
+    #[bench]
+    fn from_bytes(bencher: &mut Bencher) {
+        let bytes = DEF_HOTEL_ON_START.parse::<Ion>().unwrap().to_bytes();
+
+        bencher.iter(|| {
+            let result = Ion::from_bytes(&bytes);
+            black_box(result.unwrap())
+        })
+    }
+
+    #[bench]
+    fn reparse(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let result = DEF_HOTEL_ON_START.parse::<Ion>();
+            black_box(result.unwrap())
+        })
+    }
+}
+
 mod parse_filtered {
     use super::*;
 
@@ -1,33 +1,156 @@
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "binary")]
+mod binary;
+#[cfg(feature = "datetime")]
+pub(crate) mod datetime;
 mod display;
 mod from_ion;
 mod from_row;
 mod ion_error;
+mod pretty;
 mod section;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "toml")]
+mod to_toml;
 mod value;
 
+#[cfg(feature = "datetime")]
+pub use self::datetime::DateTime;
+pub use self::display::DisplayOpts;
 pub use self::from_ion::*;
 pub use self::from_row::*;
 pub use self::ion_error::*;
+pub use self::pretty::*;
 pub use self::section::*;
 pub use self::value::*;
-use crate::Parser;
-use std::collections::BTreeMap;
-use std::str;
+use crate::{parse_value, Dictionary, ErrorAction, Parser, ParserError, Row};
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Bound;
+use core::str;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Ion {
     sections: BTreeMap<String, Section>,
+    preamble: Option<String>,
+}
+
+/// Which of the requested sections were found, see [`Ion::from_str_filtered_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilterReport {
+    /// Requested sections present in the document, in request order.
+    pub found: Vec<String>,
+    /// Requested sections absent from the document, in request order.
+    pub not_found: Vec<String>,
 }
 
 impl Ion {
     pub fn new(sections: BTreeMap<String, Section>) -> Ion {
-        Ion { sections }
+        Ion {
+            sections,
+            preamble: None,
+        }
+    }
+
+    /// Returns a document without sections, same as `Ion::default()`.
+    pub fn new_empty() -> Ion {
+        Ion::default()
+    }
+
+    /// Parses with the given, possibly configured, parser.
+    ///
+    /// Unlike `Parser::read`, this also keeps the preamble when the parser
+    /// is configured `with_preamble`.
+    pub fn from_parser<F: FnMut(&ParserError) -> ErrorAction>(
+        parser: Parser<F>,
+    ) -> Result<Self, IonError> {
+        let preamble = parser.preamble();
+        let mut ion = parser_to_ion(parser)?;
+        ion.preamble = preamble;
+        Ok(ion)
+    }
+
+    /// Returns the comment block at the start of the document, see `Parser::preamble`.
+    pub fn preamble(&self) -> Option<&str> {
+        self.preamble.as_deref()
     }
 
     pub fn from_str_filtered(s: &str, accepted_sections: Vec<&str>) -> Result<Self, IonError> {
         parser_to_ion(Parser::new_filtered(s, accepted_sections))
     }
 
+    /// Like `from_str_filtered`, additionally reporting which of the
+    /// requested sections were found.
+    pub fn from_str_filtered_report(
+        s: &str,
+        accepted_sections: Vec<&str>,
+    ) -> Result<(Self, FilterReport), IonError> {
+        let requested: Vec<String> = accepted_sections
+            .iter()
+            .map(|&name| name.to_owned())
+            .collect();
+        let ion = Self::from_str_filtered(s, accepted_sections)?;
+
+        let (found, not_found) = requested
+            .into_iter()
+            .partition(|name| ion.sections.contains_key(name));
+
+        Ok((ion, FilterReport { found, not_found }))
+    }
+
+    /// Like `FromStr`, but on failure also returns the line and column of
+    /// the first parser error, and the text of that line.
+    pub fn parse_with_context(s: &str) -> Result<Self, IonErrorWithContext> {
+        s.parse()
+            .map_err(|error| IonErrorWithContext::new(error, s))
+    }
+
+    /// Reads and parses the file at `path`.
+    ///
+    /// Any error, including failing to read the file, is wrapped in
+    /// `IonError::InFile` naming the path. A file which isn't valid UTF-8 fails with
+    /// `IonError::InvalidUtf8`. With the `utf16` feature, a file starting with a UTF-16
+    /// byte order mark is decoded like `from_utf16_reader` does instead.
+    #[cfg(feature = "std")]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, IonError> {
+        let path = path.as_ref();
+        let in_file = |source| IonError::InFile {
+            path: path.display().to_string(),
+            source: alloc::boxed::Box::new(source),
+        };
+
+        let bytes = std::fs::read(path).map_err(|e| in_file(IonError::Io(e.to_string())))?;
+
+        #[cfg(feature = "utf16")]
+        if bytes.starts_with(UTF16_LE_BOM) || bytes.starts_with(UTF16_BE_BOM) {
+            return decode_utf16(&bytes)
+                .map_err(in_file)?
+                .parse()
+                .map_err(in_file);
+        }
+
+        let s =
+            String::from_utf8(bytes).map_err(|e| in_file(IonError::InvalidUtf8(e.utf8_error())))?;
+        s.parse().map_err(in_file)
+    }
+
+    /// Reads UTF-16 encoded input and parses it.
+    ///
+    /// The byte order is taken from the byte order mark, input without one is read
+    /// as little endian. Malformed input fails with `IonError::Encoding`.
+    #[cfg(feature = "utf16")]
+    pub fn from_utf16_reader<R: std::io::Read>(mut r: R) -> Result<Self, IonError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)
+            .map_err(|e| IonError::Io(e.to_string()))?;
+
+        decode_utf16(&bytes)?.parse()
+    }
+
     pub fn get(&self, key: &str) -> Option<&Section> {
         self.sections.get(key)
     }
@@ -40,18 +163,270 @@ impl Ion {
         self.sections.get_mut(key)
     }
 
+    /// Returns a mutable reference to the section associated with the given key,
+    /// inserting an empty section first if there is none.
+    pub fn section_entry(&mut self, key: impl Into<String>) -> &mut Section {
+        self.sections.entry(key.into()).or_default()
+    }
+
+    /// Inserts a section, returning the section previously stored under `key`.
+    pub fn insert(&mut self, key: impl Into<String>, section: Section) -> Option<Section> {
+        self.sections.insert(key.into(), section)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Returns the number of sections.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Returns the section at `index` in the order of `iter`, i.e. sorted by name.
+    ///
+    /// This walks the sections up to `index`, so paging through all of them
+    /// is better done with `iter`.
+    pub fn section_at(&self, index: usize) -> Option<(&String, &Section)> {
+        self.sections.iter().nth(index)
+    }
+
     pub fn fetch(&self, key: &str) -> Result<&Section, IonError> {
         self.get(key)
             .ok_or_else(|| IonError::MissingSection(key.to_owned()))
     }
 
+    /// Returns the string at `key` in `section`, or `None` when either is missing
+    /// or the value is not a string.
+    pub fn get_string(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_value(section, key)?.as_str()
+    }
+
+    /// Returns the integer at `key` in `section`, see `get_string`.
+    pub fn get_int(&self, section: &str, key: &str) -> Option<i64> {
+        self.get_value(section, key)?.as_integer()
+    }
+
+    /// Returns the float at `key` in `section`, see `get_string`.
+    pub fn get_float(&self, section: &str, key: &str) -> Option<f64> {
+        self.get_value(section, key)?.as_float()
+    }
+
+    /// Returns the boolean at `key` in `section`, see `get_string`.
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        self.get_value(section, key)?.as_boolean()
+    }
+
+    fn get_value(&self, section: &str, key: &str) -> Option<&Value> {
+        self.get(section)?.get(key)
+    }
+
+    /// Runs `f` on the section associated with the given key.
+    ///
+    /// Returns `IonError::MissingSection` if there is no such section.
+    pub fn apply(&mut self, key: &str, f: impl FnOnce(&mut Section)) -> Result<(), IonError> {
+        let section = self
+            .get_mut(key)
+            .ok_or_else(|| IonError::MissingSection(key.to_owned()))?;
+        f(section);
+        Ok(())
+    }
+
+    /// Fills the entries of the section `name` into every other section lacking them,
+    /// then drops that section when `remove` is `true`.
+    ///
+    /// Runs on the parsed document, so the defaults apply regardless of where their
+    /// section appears in the input. Keys already present are kept as they are,
+    /// dictionaries included, and rows are never touched.
+    ///
+    /// Returns `IonError::MissingSection` if there is no such section.
+    pub fn apply_defaults_section(&mut self, name: &str, remove: bool) -> Result<(), IonError> {
+        let defaults = self
+            .sections
+            .remove(name)
+            .ok_or_else(|| IonError::MissingSection(name.to_owned()))?;
+
+        for section in self.sections.values_mut() {
+            section.merge_dictionary(&defaults.dictionary, false);
+        }
+
+        if !remove {
+            self.sections.insert(name.to_owned(), defaults);
+        }
+
+        Ok(())
+    }
+
     pub fn remove(&mut self, key: &str) -> Option<Section> {
         self.sections.remove(key)
     }
 
+    /// Consumes the document, returning the section associated with the given key
+    /// and dropping all others.
+    pub fn into_section(mut self, key: &str) -> Option<Section> {
+        self.sections.remove(key)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Section)> {
         self.sections.iter()
     }
+
+    /// Calls `f` for every section in order, stopping at the first error and returning it.
+    pub fn try_for_each_section<E>(
+        &self,
+        mut f: impl FnMut(&str, &Section) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.sections
+            .iter()
+            .try_for_each(|(name, section)| f(name, section))
+    }
+
+    /// Returns the sections matching `pattern`, in name order.
+    ///
+    /// Only a trailing `*` is supported, so `item*` matches every section whose name
+    /// starts with `item`. Any other pattern must match a section name exactly.
+    pub fn sections_matching(&self, pattern: &str) -> Vec<(&String, &Section)> {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => self
+                .sections
+                .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+                .take_while(|(name, _)| name.starts_with(prefix))
+                .collect(),
+            None => self.sections.get_key_value(pattern).into_iter().collect(),
+        }
+    }
+
+    /// Fails with `IonError::UnknownSections`, naming every section not in `allowed`.
+    ///
+    /// Content outside of any section forms the `root` section, which has to be
+    /// allowed like any other.
+    pub fn validate_no_unknown_sections(&self, allowed: &[&str]) -> Result<(), IonError> {
+        let unknown: Vec<String> = self
+            .sections
+            .keys()
+            .filter(|name| !allowed.contains(&name.as_str()))
+            .cloned()
+            .collect();
+
+        match unknown.is_empty() {
+            true => Ok(()),
+            false => Err(IonError::UnknownSections(unknown)),
+        }
+    }
+
+    /// Removes the sections whose `when` entry evaluates to false against `context`.
+    ///
+    /// A condition is a string of the form `key == value` or `key != value`, where `key`
+    /// is looked up in `context` and `value` is a literal as written in an entry,
+    /// e.g. `when = "env == \"prod\""`. A key missing from `context` equals no value.
+    /// Sections without `when` are kept, kept sections keep their `when` entry.
+    ///
+    /// Fails with `IonError::InvalidCondition` when a condition can't be read,
+    /// leaving the document unchanged.
+    pub fn resolve_conditionals(&mut self, context: &Dictionary) -> Result<(), IonError> {
+        let mut excluded = Vec::new();
+
+        for (name, section) in &self.sections {
+            if let Some(when) = section.get(Section::WHEN_KEY) {
+                match when.as_str().and_then(|expr| eval_condition(expr, context)) {
+                    Some(true) => {}
+                    Some(false) => excluded.push(name.clone()),
+                    None => {
+                        return Err(IonError::InvalidCondition {
+                            section: name.clone(),
+                            expression: when.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        for name in excluded {
+            self.sections.remove(&name);
+        }
+
+        Ok(())
+    }
+
+    /// Hashes the logical content, i.e. section names, attributes, entries and rows,
+    /// for detecting changes between parses.
+    ///
+    /// Documents differing only in formatting, comments or the preamble hash equally.
+    /// The content is fed to the hash in a fixed byte encoding rather than through
+    /// `Hash`, so the hash is the same across runs, targets and compiler versions.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+
+        hasher.len(self.sections.len());
+        for (name, section) in &self.sections {
+            hasher.str(name);
+            hasher.dictionary(&section.attributes);
+            hasher.dictionary(&section.dictionary);
+            hasher.len(section.rows.len());
+            for row in &section.rows {
+                hasher.row(row);
+            }
+        }
+
+        hasher.0
+    }
+
+    /// Number of content rows across all sections, header rows excluded.
+    pub fn count_rows_total(&self) -> usize {
+        self.sections
+            .values()
+            .map(|section| section.rows_without_header().len())
+            .sum()
+    }
+
+    /// Iterates the content rows of all sections in order, header rows excluded,
+    /// each paired with the name of its section.
+    pub fn rows_iter(&self) -> impl Iterator<Item = (&str, &Row)> {
+        self.sections.iter().flat_map(|(name, section)| {
+            section
+                .rows_without_header()
+                .iter()
+                .map(move |row| (name.as_str(), row))
+        })
+    }
+
+    /// Number of dictionary entries across all sections.
+    pub fn count_entries_total(&self) -> usize {
+        self.sections
+            .values()
+            .map(|section| section.dictionary.len())
+            .sum()
+    }
+
+    /// Applies `f` to every leaf value of the document: dictionary entries and table cells
+    /// (header rows included) of all sections.
+    ///
+    /// Arrays and dictionaries are never passed to `f` themselves, only their elements are,
+    /// recursively.
+    pub fn map_values(&mut self, mut f: impl FnMut(&mut Value)) {
+        for section in self.sections.values_mut() {
+            for value in section.dictionary.values_mut() {
+                value.map_leaves(&mut f);
+            }
+
+            for cell in section.rows.iter_mut().flatten() {
+                cell.map_leaves(&mut f);
+            }
+        }
+    }
+}
+
+impl FromIterator<(String, Section)> for Ion {
+    fn from_iter<I: IntoIterator<Item = (String, Section)>>(iter: I) -> Self {
+        Ion::new(iter.into_iter().collect())
+    }
+}
+
+/// Sections with a name already present replace the existing ones.
+impl Extend<(String, Section)> for Ion {
+    fn extend<I: IntoIterator<Item = (String, Section)>>(&mut self, iter: I) {
+        self.sections.extend(iter)
+    }
 }
 
 impl str::FromStr for Ion {
@@ -62,7 +437,178 @@ impl str::FromStr for Ion {
     }
 }
 
-fn parser_to_ion(mut parser: Parser) -> Result<Ion, IonError> {
+/// Unlike `FromStr`, reports where in the input parsing failed, see `Ion::parse_with_context`.
+impl TryFrom<&str> for Ion {
+    type Error = IonErrorWithContext;
+
+    fn try_from(s: &str) -> Result<Ion, IonErrorWithContext> {
+        Ion::parse_with_context(s)
+    }
+}
+
+/// Parses raw bytes, failing with `IonError::InvalidUtf8` when they are not valid UTF-8.
+///
+/// No input makes this panic or recurse without bound, which makes it a suitable fuzzing target.
+pub fn parse_bytes(input: &[u8]) -> Result<Ion, IonError> {
+    str::from_utf8(input)
+        .map_err(IonError::InvalidUtf8)?
+        .parse()
+}
+
+/// The 64-bit FNV-1a hash, see `Ion::content_hash`.
+struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+/// Lengths are written as `u64` and numbers in little endian, each value prefixed
+/// by a tag byte, so that the hash doesn't depend on the target.
+impl Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn len(&mut self, n: usize) {
+        self.write(&(n as u64).to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.write(s.as_bytes());
+    }
+
+    fn dictionary(&mut self, dictionary: &Dictionary) {
+        self.len(dictionary.len());
+        for (key, value) in dictionary {
+            self.str(key);
+            self.value(value);
+        }
+    }
+
+    fn row(&mut self, row: &Row) {
+        self.len(row.len());
+        for value in row {
+            self.value(value);
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::String(v) => {
+                self.write(&[0]);
+                self.str(v);
+            }
+            Value::Integer(v) => {
+                self.write(&[1]);
+                self.write(&v.to_le_bytes());
+            }
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) => {
+                self.write(&[2]);
+                self.write(&v.to_le_bytes());
+            }
+            Value::Float(v) => {
+                // `0.0` and `-0.0` are equal, so they hash equally.
+                let v = if *v == 0.0 { 0.0 } else { *v };
+                self.write(&[3]);
+                self.write(&v.to_bits().to_le_bytes());
+            }
+            Value::Boolean(v) => self.write(&[4, u8::from(*v)]),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(v) => {
+                self.write(&[5]);
+                self.write(&v.year.to_le_bytes());
+                self.write(&[v.month, v.day, v.hour, v.minute, v.second]);
+                self.write(&v.nanosecond.to_le_bytes());
+                self.write(&v.offset_minutes.to_le_bytes());
+            }
+            Value::Array(v) => {
+                self.write(&[6]);
+                self.row(v);
+            }
+            Value::Dictionary(v) => {
+                self.write(&[7]);
+                self.dictionary(v);
+            }
+            Value::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                self.write(&[8]);
+                self.write(&start.to_le_bytes());
+                self.write(&end.to_le_bytes());
+                self.write(&[u8::from(*inclusive)]);
+            }
+            Value::Raw(v) => {
+                self.write(&[9]);
+                self.str(v);
+            }
+        }
+    }
+}
+
+/// Evaluates `key == value` or `key != value`, see `Ion::resolve_conditionals`.
+fn eval_condition(expr: &str, context: &Dictionary) -> Option<bool> {
+    let (at, negated) = match (expr.find("=="), expr.find("!=")) {
+        (Some(eq), Some(ne)) => (eq.min(ne), ne < eq),
+        (Some(eq), None) => (eq, false),
+        (None, Some(ne)) => (ne, true),
+        (None, None) => return None,
+    };
+
+    let key = expr[..at].trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return None;
+    }
+
+    let value = parse_value(expr[at + 2..].trim()).ok()?;
+
+    Some((context.get(key) == Some(&value)) != negated)
+}
+
+#[cfg(feature = "utf16")]
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+#[cfg(feature = "utf16")]
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Decodes UTF-16 by its byte order mark, little endian when there is none.
+#[cfg(feature = "utf16")]
+fn decode_utf16(bytes: &[u8]) -> Result<String, IonError> {
+    let (bytes, from_bytes): (_, fn([u8; 2]) -> u16) = match bytes {
+        [0xFE, 0xFF, rest @ ..] => (rest, u16::from_be_bytes),
+        [0xFF, 0xFE, rest @ ..] => (rest, u16::from_le_bytes),
+        _ => (bytes, u16::from_le_bytes),
+    };
+
+    if bytes.len() % 2 != 0 {
+        return Err(IonError::Encoding(
+            "UTF-16 input has an odd length".to_owned(),
+        ));
+    }
+
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| IonError::Encoding(e.to_string()))
+}
+
+fn parser_to_ion<F: FnMut(&ParserError) -> ErrorAction>(
+    mut parser: Parser<F>,
+) -> Result<Ion, IonError> {
+    parser.check_input_size()?;
+
     match parser.read() {
         Some(ion) => Ok(Ion::new(ion)),
         None => Err(IonError::ParserErrors(parser.errors)),
@@ -84,7 +630,7 @@ macro_rules! ion_filtered {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Ion, Value};
+    use crate::{Dictionary, Ion, IonError, Parser, Section, Value};
 
     #[test]
     fn as_string() {
@@ -113,6 +659,15 @@ mod tests {
         assert_eq!(None, v.as_integer());
     }
 
+    #[test]
+    fn as_i128() {
+        let v = Value::Integer(-1);
+        assert_eq!(Some(-1), v.as_i128());
+
+        let v = Value::String("1".into());
+        assert_eq!(None, v.as_i128());
+    }
+
     #[test]
     fn as_str() {
         let v = Value::String("foo".into());
@@ -167,6 +722,66 @@ mod tests {
         assert_eq!(0, rows.len());
     }
 
+    #[test]
+    fn apply() {
+        let mut ion = ion!(
+            r#"
+            [FOO]
+            |1|2|
+        "#
+        );
+
+        ion.apply("FOO", |section| {
+            section
+                .rows
+                .push(vec![Value::new_string("3"), Value::new_string("4")])
+        })
+        .unwrap();
+
+        assert_eq!("[FOO]\n| 1 | 2 |\n| 3 | 4 |\n\n", ion.to_string());
+
+        let err = ion.apply("BAR", |_| unreachable!()).unwrap_err();
+        assert!(matches!(err, IonError::MissingSection(name) if name == "BAR"));
+    }
+
+    #[test]
+    fn map_values() {
+        let mut ion = ion!(
+            r#"
+            [FOO]
+            name = "foo"
+            tags = ["a", 1, { nested = "b" }]
+            | head |
+            |------|
+            | cell |
+            [BAR]
+            count = 2
+            "#
+        );
+
+        ion.map_values(|v| {
+            if let Value::String(s) = v {
+                *s = s.to_uppercase();
+            }
+        });
+
+        let foo = ion.get("FOO").unwrap();
+        assert_eq!(Some("FOO"), foo.get("name").and_then(Value::as_str));
+        assert_eq!(
+            "[ \"A\", 1, { nested = \"B\" } ]",
+            foo.get("tags").unwrap().to_string()
+        );
+        assert_eq!(Some("HEAD"), foo.rows[0][0].as_str());
+        assert_eq!(Some("CELL"), foo.rows_without_header()[0][0].as_str());
+        assert_eq!(
+            Some(2),
+            ion.get("BAR")
+                .unwrap()
+                .get("count")
+                .and_then(Value::as_integer)
+        );
+    }
+
     #[test]
     fn filtered_section() {
         let ion = ion_filtered!(
@@ -185,4 +800,481 @@ mod tests {
         assert_eq!(3, rows.len());
         assert!(ion.get("BAR").is_none());
     }
+
+    /// Returns a path in the temporary directory unique to this process and call,
+    /// so that tests running in parallel or overlapping runs don't share files.
+    #[cfg(feature = "std")]
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("ion_{}_{n}_{name}", std::process::id()))
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_path_names_file_in_error() {
+        let path = temp_path("from_path_names_file_in_error.ion");
+        std::fs::write(&path, "[CONTRACT]\nkey =\n").unwrap();
+
+        let err = Ion::from_path(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(err, IonError::InFile { .. }));
+        assert!(err.to_string().contains(&path.display().to_string()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_path_missing_file() {
+        let err = Ion::from_path("does/not/exist.ion").unwrap_err();
+
+        match err {
+            IonError::InFile { path, source } => {
+                assert_eq!("does/not/exist.ion", path);
+                assert!(matches!(*source, IonError::Io(_)));
+            }
+            _ => panic!("expected IonError::InFile, got {err:?}"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_path_invalid_utf8() {
+        let path = temp_path("from_path_invalid_utf8.ion");
+        std::fs::write(&path, b"[CONTRACT]\nkey = \"\xFF\"\n").unwrap();
+
+        let err = Ion::from_path(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        match err {
+            IonError::InFile { source, .. } => {
+                assert!(matches!(*source, IonError::InvalidUtf8(_)))
+            }
+            _ => panic!("expected IonError::InFile, got {err:?}"),
+        }
+    }
+
+    #[cfg(feature = "utf16")]
+    fn utf16_le(s: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[cfg(feature = "utf16")]
+    #[test]
+    fn from_utf16_reader() {
+        let raw = "[CONTRACT]\nname = \"Hôtel\"\n";
+
+        let ion = Ion::from_utf16_reader(&utf16_le(raw)[..]).unwrap();
+        assert_eq!(Some("Hôtel"), ion.get_string("CONTRACT", "name"));
+
+        let no_bom: Vec<u8> = raw.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(ion, Ion::from_utf16_reader(&no_bom[..]).unwrap());
+
+        let err = Ion::from_utf16_reader(&[0xFF, 0xFE, 0x00, 0xD8][..]).unwrap_err();
+        assert!(matches!(err, IonError::Encoding(_)));
+    }
+
+    #[cfg(feature = "utf16")]
+    #[test]
+    fn from_path_detects_utf16() {
+        let path = temp_path("from_path_detects_utf16.ion");
+        std::fs::write(&path, utf16_le("[CONTRACT]\nkey = 1\n")).unwrap();
+
+        let ion = Ion::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Some(1), ion.unwrap().get_int("CONTRACT", "key"));
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            key = 1
+            [BAR]
+            key = 2
+            [BAZ]
+            key = 3
+        "#
+        );
+
+        let mut collected: Ion = ion
+            .iter()
+            .filter(|(name, _)| name.starts_with("BA"))
+            .map(|(name, section)| (name.clone(), section.clone()))
+            .collect();
+
+        assert!(collected.get("FOO").is_none());
+        assert!(collected.get("BAR").is_some());
+        assert!(collected.get("BAZ").is_some());
+
+        let mut foo = ion.get("FOO").unwrap().clone();
+        foo.dictionary.insert("key".to_owned(), Value::Integer(4));
+        collected.extend(vec![
+            ("FOO".to_owned(), foo.clone()),
+            ("BAR".to_owned(), foo),
+        ]);
+
+        assert_eq!(3, collected.iter().count());
+        assert_eq!(
+            Some(4),
+            collected
+                .get("BAR")
+                .unwrap()
+                .get("key")
+                .and_then(Value::as_integer)
+        );
+    }
+
+    #[test]
+    fn count_totals() {
+        let ion = ion!(
+            r#"
+            [CONTRACT]
+            id = "HOTEL001"
+            currency = "EUR"
+
+            [BOARD]
+            | code | description |
+            |------|-------------|
+            | RO   | Room Only   |
+            | BB   | Breakfast   |
+
+            [ROOMS]
+            count = 2
+            | DBL | Double |
+            | SGL | Single |
+            | TPL | Triple |
+        "#
+        );
+
+        assert_eq!(5, ion.count_rows_total());
+        assert_eq!(3, ion.count_entries_total());
+    }
+
+    #[test]
+    fn rows_iter() {
+        let ion = ion!(
+            r#"
+            [BOARD]
+            | code | description |
+            |------|-------------|
+            | RO   | Room Only   |
+            | BB   | Breakfast   |
+
+            [ROOMS]
+            count = 2
+            | DBL | Double |
+            | SGL | Single |
+            | TPL | Triple |
+        "#
+        );
+
+        let rows: Vec<(&str, &str)> = ion
+            .rows_iter()
+            .map(|(name, row)| (name, row[0].as_str().unwrap()))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("BOARD", "RO"),
+                ("BOARD", "BB"),
+                ("ROOMS", "DBL"),
+                ("ROOMS", "SGL"),
+                ("ROOMS", "TPL"),
+            ],
+            rows
+        );
+        assert_eq!(ion.count_rows_total(), ion.rows_iter().count());
+    }
+
+    #[test]
+    fn apply_defaults_section() {
+        let mut ion = ion!(
+            r#"
+            [HOTEL]
+            name = "Hotel"
+
+            [DEFAULTS]
+            currency = "USD"
+            nights = 1
+
+            [CONTRACT]
+            currency = "EUR"
+
+            [RATES]
+            room = "DBL"
+        "#
+        );
+
+        ion.apply_defaults_section("DEFAULTS", false).unwrap();
+
+        assert_eq!(Some("USD"), ion.get_string("HOTEL", "currency"));
+        assert_eq!(Some("USD"), ion.get_string("RATES", "currency"));
+        assert_eq!(Some("EUR"), ion.get_string("CONTRACT", "currency"));
+        assert_eq!(Some(1), ion.get_int("CONTRACT", "nights"));
+        assert_eq!(Some(1), ion.get_int("HOTEL", "nights"));
+        assert_eq!(Some("USD"), ion.get_string("DEFAULTS", "currency"));
+
+        ion.apply_defaults_section("DEFAULTS", true).unwrap();
+        assert!(ion.get("DEFAULTS").is_none());
+        assert!(matches!(
+            ion.apply_defaults_section("DEFAULTS", true),
+            Err(IonError::MissingSection(name)) if name == "DEFAULTS"
+        ));
+    }
+
+    #[test]
+    fn parse_bytes() {
+        let ion = crate::parse_bytes(b"[FOO]\nkey = 1").unwrap();
+        assert_eq!(
+            Some(1),
+            ion.get("FOO")
+                .unwrap()
+                .get("key")
+                .and_then(Value::as_integer)
+        );
+
+        let err = crate::parse_bytes(b"[FOO]\nkey = \"\xff\"").unwrap_err();
+        assert!(matches!(err, IonError::InvalidUtf8(e) if e.valid_up_to() == 13));
+    }
+
+    #[test]
+    fn into_section() {
+        let raw = r#"
+            [FOO]
+            key = 1
+            [BAR]
+            key = 2
+            [BAZ]
+            key = 3
+        "#;
+
+        let bar = ion!(raw).into_section("BAR").unwrap();
+        assert_eq!(Some(2), bar.get("key").and_then(Value::as_integer));
+
+        assert!(ion!(raw).into_section("QUX").is_none());
+    }
+
+    #[test]
+    fn section_entry() {
+        let mut ion = ion!("[FOO]\nkey = 1");
+
+        ion.section_entry("BAR")
+            .rows
+            .push(vec![Value::new_string("cell")]);
+        assert_eq!(1, ion.get("BAR").unwrap().rows.len());
+
+        let foo = ion.section_entry("FOO");
+        assert_eq!(Some(1), foo.get("key").and_then(Value::as_integer));
+        foo.dictionary.insert("other".to_owned(), Value::Integer(2));
+
+        assert_eq!(2, ion.get("FOO").unwrap().dictionary.len());
+        assert_eq!(2, ion.iter().count());
+    }
+
+    #[test]
+    fn new_empty() {
+        let mut ion = Ion::default();
+        assert!(ion.is_empty());
+        assert_eq!(Ion::new_empty(), ion);
+
+        let mut section = Section::new();
+        section
+            .dictionary
+            .insert("key".to_owned(), Value::Integer(1));
+        assert!(ion.insert("FOO", section).is_none());
+
+        assert!(!ion.is_empty());
+        assert_eq!(Some(1), ion.get_int("FOO", "key"));
+        assert_eq!("[FOO]\nkey = 1\n\n", ion.to_string());
+    }
+
+    #[test]
+    fn section_at() {
+        let ion = ion!("[FOO]\n[BAR]\nkey = 1\n[BAZ]");
+
+        assert_eq!(3, ion.len());
+
+        let (name, section) = ion.section_at(1).unwrap();
+        assert_eq!("BAZ", name);
+        assert!(section.dictionary.is_empty());
+        assert_eq!(
+            Some("BAR"),
+            ion.section_at(0).map(|(name, _)| name.as_str())
+        );
+        assert!(ion.section_at(3).is_none());
+    }
+
+    #[test]
+    fn from_str_filtered_report() {
+        let raw = "[FOO]\nkey = 1\n[BAR]\nkey = 2\n[BAZ]\nkey = 3";
+
+        let (ion, report) = Ion::from_str_filtered_report(raw, vec!["FOO", "QUX", "BAZ"]).unwrap();
+
+        assert_eq!(2, ion.iter().count());
+        assert_eq!(vec!["FOO", "BAZ"], report.found);
+        assert_eq!(vec!["QUX"], report.not_found);
+    }
+
+    #[test]
+    fn preamble() {
+        let raw = "# Hotel definitions\n# generated\n[FOO]\nkey = 1\n";
+
+        let ion = Ion::from_parser(Parser::new(raw).with_preamble(true)).unwrap();
+        assert_eq!(Some(" Hotel definitions\n generated"), ion.preamble());
+        assert!(ion.get("FOO").is_some());
+
+        assert_eq!(None, ion!(raw).preamble());
+    }
+
+    #[test]
+    fn typed_getters() {
+        let ion = ion!("[FOO]\nname = \"foo\"\ncount = 3\nratio = 0.5\nenabled = true");
+
+        assert_eq!(Some("foo"), ion.get_string("FOO", "name"));
+        assert_eq!(Some(3), ion.get_int("FOO", "count"));
+        assert_eq!(Some(0.5), ion.get_float("FOO", "ratio"));
+        assert_eq!(Some(true), ion.get_bool("FOO", "enabled"));
+
+        assert_eq!(None, ion.get_int("FOO", "name"));
+        assert_eq!(None, ion.get_string("FOO", "missing"));
+        assert_eq!(None, ion.get_bool("BAR", "enabled"));
+    }
+
+    #[test]
+    fn resolve_conditionals() {
+        let raw = r#"
+            [COMMON]
+            key = 1
+            [PROD]
+            when = "env == \"prod\""
+            [DEV]
+            when = "env != \"prod\""
+            [DEBUG]
+            when = "debug == true"
+        "#;
+        let mut context = Dictionary::new();
+        context.insert("env".to_owned(), Value::new_string("prod"));
+
+        let mut ion = ion!(raw);
+        ion.resolve_conditionals(&context).unwrap();
+        let names: Vec<_> = ion.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["COMMON", "PROD"], names);
+
+        context.insert("env".to_owned(), Value::new_string("dev"));
+        context.insert("debug".to_owned(), Value::Boolean(true));
+
+        let mut ion = ion!(raw);
+        ion.resolve_conditionals(&context).unwrap();
+        let names: Vec<_> = ion.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["COMMON", "DEBUG", "DEV"], names);
+    }
+
+    #[test]
+    fn resolve_conditionals_fails_on_invalid_condition() {
+        let mut ion = ion!("[FOO]\nwhen = \"env = prod\"\n[BAR]\nwhen = \"env == \\\"prod\\\"\"");
+
+        match ion.resolve_conditionals(&Dictionary::new()) {
+            Err(IonError::InvalidCondition {
+                section,
+                expression,
+            }) => {
+                assert_eq!("FOO", section);
+                assert_eq!("env = prod", expression);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(2, ion.len());
+    }
+
+    #[test]
+    fn from_parser_with_max_input_size() {
+        let raw = "[FOO]\nkey = 1\n";
+
+        match Ion::from_parser(Parser::new(raw).with_max_input_size(10)) {
+            Err(IonError::InputTooLarge { size, max }) => assert_eq!((raw.len(), 10), (size, max)),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let ion = Ion::from_parser(Parser::new(raw).with_max_input_size(raw.len())).unwrap();
+        assert_eq!(Some(1), ion.get_int("FOO", "key"));
+    }
+
+    #[test]
+    fn try_for_each_section() {
+        let ion = ion!("[A]\n[B]\n[C]");
+        let mut visited = Vec::new();
+
+        let result = ion.try_for_each_section(|name, _| {
+            visited.push(name.to_owned());
+            match name {
+                "B" => Err(name.to_owned()),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(Err("B".to_owned()), result);
+        assert_eq!(vec!["A", "B"], visited);
+        assert_eq!(Ok(()), ion.try_for_each_section(|_, _| Ok::<_, ()>(())));
+    }
+
+    #[test]
+    fn content_hash() {
+        let ion = ion!("[FOO]\nkey = 1\n| a | b |\n[BAR]\nlist = [1, 2]");
+        let reformatted = ion!("# comment\n[FOO]\n  key=1\n|a|b|\n\n[BAR]\nlist = [ 1,2 ]\n");
+
+        assert_eq!(ion.content_hash(), reformatted.content_hash());
+        assert_eq!(ion.content_hash(), ion.clone().content_hash());
+        assert_ne!(
+            ion.content_hash(),
+            ion!("[FOO]\nkey = 2\n| a | b |\n[BAR]\nlist = [1, 2]").content_hash()
+        );
+        assert_ne!(
+            ion.content_hash(),
+            ion!("[FOO]\nkey = 1\n| a | b |\n[BAZ]\nlist = [1, 2]").content_hash()
+        );
+        // Pinned, as the hash must not change between targets or compiler versions.
+        assert_eq!(
+            14_186_656_340_874_169_734,
+            ion!("[FOO]\nkey = 1\n| a | b |\n").content_hash()
+        );
+    }
+
+    #[test]
+    fn validate_no_unknown_sections() {
+        let ion = ion!("[HOTEL]\n[Hotle]\n[CONTRACT]\n");
+
+        assert!(
+            ion.validate_no_unknown_sections(&["HOTEL", "Hotle", "CONTRACT"])
+                .is_ok()
+        );
+
+        match ion.validate_no_unknown_sections(&["HOTEL", "CONTRACT", "RATES"]) {
+            Err(IonError::UnknownSections(names)) => assert_eq!(vec!["Hotle"], names),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sections_matching() {
+        let ion = ion!("[item1]\n[other]\n[item2]\n[it]");
+
+        let names = |pattern| -> Vec<&str> {
+            ion.sections_matching(pattern)
+                .into_iter()
+                .map(|(name, _)| name.as_str())
+                .collect()
+        };
+
+        assert_eq!(vec!["item1", "item2"], names("item*"));
+        assert_eq!(vec!["other"], names("other"));
+        assert_eq!(vec!["it", "item1", "item2", "other"], names("*"));
+        assert!(names("item").is_empty());
+    }
 }
@@ -0,0 +1,150 @@
+use crate::{Dictionary, Ion, Row, Section, Value};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// Nesting depth of generated arrays and dictionaries.
+const MAX_DEPTH: usize = 3;
+
+/// Number of elements of generated collections, kept small so trees stay readable.
+const MAX_LEN: usize = 4;
+
+/// Generates any value but `BigInt`, `DateTime`, `Range` and `Raw`, with arrays and dictionaries nested at most
+/// `MAX_DEPTH` levels deep. Dictionary keys are valid entry keys.
+impl<'a> Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        value(u, 0)
+    }
+}
+
+/// Generates a section with a dictionary and rows of string cells, without attributes.
+impl<'a> Arbitrary<'a> for Section {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut section = Section::new();
+        section.dictionary = dictionary(u, 0)?;
+
+        for _ in 0..u.int_in_range(0..=MAX_LEN)? {
+            let mut row = Row::new();
+            for _ in 0..u.int_in_range(1..=MAX_LEN)? {
+                row.push(Value::String(u.arbitrary()?));
+            }
+            section.rows.push(row);
+        }
+
+        Ok(section)
+    }
+}
+
+/// Generates a document whose section names are valid keys, without a preamble.
+impl<'a> Arbitrary<'a> for Ion {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut sections = BTreeMap::new();
+
+        for _ in 0..u.int_in_range(0..=MAX_LEN)? {
+            sections.insert(key(u)?, u.arbitrary()?);
+        }
+
+        Ok(Ion::new(sections))
+    }
+}
+
+fn value(u: &mut Unstructured, depth: usize) -> Result<Value> {
+    let last_variant = if depth < MAX_DEPTH { 5 } else { 3 };
+
+    Ok(match u.int_in_range(0..=last_variant)? {
+        0 => Value::String(u.arbitrary()?),
+        1 => Value::Integer(u.arbitrary()?),
+        2 => Value::Float(u.arbitrary()?),
+        3 => Value::Boolean(u.arbitrary()?),
+        4 => {
+            let mut array = Vec::new();
+            for _ in 0..u.int_in_range(0..=MAX_LEN)? {
+                array.push(value(u, depth + 1)?);
+            }
+            Value::Array(array)
+        }
+        _ => Value::Dictionary(dictionary(u, depth + 1)?),
+    })
+}
+
+fn dictionary(u: &mut Unstructured, depth: usize) -> Result<Dictionary> {
+    let mut dictionary = Dictionary::new();
+
+    for _ in 0..u.int_in_range(0..=MAX_LEN)? {
+        dictionary.insert(key(u)?, value(u, depth)?);
+    }
+
+    Ok(dictionary)
+}
+
+fn key(u: &mut Unstructured) -> Result<String> {
+    const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-";
+
+    (0..u.int_in_range(1..=8)?)
+        .map(|_| u.choose(CHARS).map(|&c| char::from(c)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Ion, Value};
+    use arbitrary::{Arbitrary, Unstructured};
+
+    /// Deterministic pseudo-random input for `Unstructured`.
+    fn bytes(seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..4096)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    /// Non-finite floats have no `.ion` syntax, see `Ion::to_ion_string`.
+    fn round_trips(value: &Value) -> bool {
+        match value {
+            Value::Float(v) => v.is_finite(),
+            Value::Array(values) => values.iter().all(round_trips),
+            Value::Dictionary(d) => d.values().all(round_trips),
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn values_round_trip_where_applicable() {
+        let mut checked = 0;
+
+        for seed in 0..256 {
+            let bytes = bytes(seed);
+            let value = Value::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            if !round_trips(&value) {
+                continue;
+            }
+
+            let raw = format!("key = {value:#}");
+            let parsed = raw.parse::<Ion>().unwrap();
+            assert_eq!(
+                Some(&value),
+                parsed.get("root").unwrap().get("key"),
+                "{raw}"
+            );
+            checked += 1;
+        }
+
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn documents_display_without_panicking_on_parse() {
+        for seed in 0..256 {
+            let bytes = bytes(seed);
+            let ion = Ion::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+
+            let _ = ion.to_string().parse::<Ion>();
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! A compact binary form of a parsed document, see `Ion::to_bytes`.
+//!
+//! Lengths and integers are LEB128 varints, signed integers zigzag encoded, floats
+//! their little-endian bits and strings UTF-8 prefixed by their length. Maps are
+//! written as a count followed by their pairs, in key order.
+
+#[cfg(feature = "datetime")]
+use crate::DateTime;
+use crate::{Dictionary, Ion, IonError, Row, Section, Value};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str;
+
+const MAGIC: &[u8] = b"ION\0";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const STRING: u8 = 0;
+const INTEGER: u8 = 1;
+#[cfg(feature = "i128")]
+const BIG_INT: u8 = 2;
+const FLOAT: u8 = 3;
+const BOOLEAN: u8 = 4;
+#[cfg(feature = "datetime")]
+const DATE_TIME: u8 = 5;
+const ARRAY: u8 = 6;
+const DICTIONARY: u8 = 7;
+const RANGE: u8 = 8;
+const RAW: u8 = 9;
+
+/// How deep arrays and dictionaries may be nested in the bytes read by `Ion::from_bytes`.
+const MAX_DEPTH: usize = 128;
+
+impl Ion {
+    /// Writes the document in a compact binary form, read back by `Ion::from_bytes`
+    /// much faster than reparsing the text, e.g. for caching large documents.
+    ///
+    /// Everything compared by `PartialEq` is kept, spans and trailing comments
+    /// included. The form is only readable by the same version of this crate.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        write_str(&mut out, VERSION);
+
+        match &self.preamble {
+            Some(preamble) => {
+                out.push(1);
+                write_str(&mut out, preamble);
+            }
+            None => out.push(0),
+        }
+
+        write_len(&mut out, self.sections.len());
+        for (name, section) in &self.sections {
+            write_str(&mut out, name);
+            write_section(&mut out, section);
+        }
+
+        out
+    }
+
+    /// Reads a document written by `Ion::to_bytes`.
+    ///
+    /// Fails with `IonError::Encoding` when the bytes are truncated, malformed, nested
+    /// more than 128 levels deep or written by another version of this crate, and with
+    /// `IonError::InvalidUtf8` when a string isn't valid UTF-8.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Ion, IonError> {
+        let mut reader = Reader {
+            bytes,
+            pos: 0,
+            depth: 0,
+        };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(IonError::Encoding("not an Ion binary".to_string()));
+        }
+
+        let version = reader.str()?;
+        if version != VERSION {
+            return Err(IonError::Encoding(format!(
+                "written by version {version}, expected {VERSION}"
+            )));
+        }
+
+        let preamble = match reader.bool()? {
+            true => Some(reader.string()?),
+            false => None,
+        };
+
+        let mut sections = BTreeMap::new();
+        for _ in 0..reader.len()? {
+            let name = reader.string()?;
+            sections.insert(name, reader.section()?);
+        }
+
+        match reader.pos == bytes.len() {
+            true => Ok(Ion { sections, preamble }),
+            false => Err(IonError::Encoding("trailing bytes".to_string())),
+        }
+    }
+}
+
+fn write_section(out: &mut Vec<u8>, section: &Section) {
+    write_dictionary(out, &section.dictionary);
+
+    write_len(out, section.rows.len());
+    for row in &section.rows {
+        write_row(out, row);
+    }
+
+    write_dictionary(out, &section.attributes);
+
+    match section.span {
+        Some((start, end)) => {
+            out.push(1);
+            write_len(out, start);
+            write_len(out, end);
+        }
+        None => out.push(0),
+    }
+
+    write_len(out, section.entry_comments.len());
+    for (key, comment) in &section.entry_comments {
+        write_str(out, key);
+        write_str(out, comment);
+    }
+
+    write_len(out, section.row_comments.len());
+    for (index, comment) in &section.row_comments {
+        write_len(out, *index);
+        write_str(out, comment);
+    }
+}
+
+fn write_dictionary(out: &mut Vec<u8>, dictionary: &Dictionary) {
+    write_len(out, dictionary.len());
+    for (key, value) in dictionary {
+        write_str(out, key);
+        write_value(out, value);
+    }
+}
+
+fn write_row(out: &mut Vec<u8>, row: &Row) {
+    write_len(out, row.len());
+    for value in row {
+        write_value(out, value);
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::String(v) => {
+            out.push(STRING);
+            write_str(out, v);
+        }
+        Value::Integer(v) => {
+            out.push(INTEGER);
+            write_int(out, *v);
+        }
+        #[cfg(feature = "i128")]
+        Value::BigInt(v) => {
+            out.push(BIG_INT);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::Float(v) => {
+            out.push(FLOAT);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::Boolean(v) => {
+            out.push(BOOLEAN);
+            out.push(u8::from(*v));
+        }
+        #[cfg(feature = "datetime")]
+        Value::DateTime(v) => {
+            out.push(DATE_TIME);
+            write_u64(out, v.year.into());
+            out.extend_from_slice(&[v.month, v.day, v.hour, v.minute, v.second]);
+            write_u64(out, v.nanosecond.into());
+            write_int(out, v.offset_minutes.into());
+        }
+        Value::Array(v) => {
+            out.push(ARRAY);
+            write_row(out, v);
+        }
+        Value::Dictionary(v) => {
+            out.push(DICTIONARY);
+            write_dictionary(out, v);
+        }
+        Value::Range {
+            start,
+            end,
+            inclusive,
+        } => {
+            out.push(RANGE);
+            write_int(out, *start);
+            write_int(out, *end);
+            out.push(u8::from(*inclusive));
+        }
+        Value::Raw(v) => {
+            out.push(RAW);
+            write_str(out, v);
+        }
+    }
+}
+
+fn write_u64(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn write_len(out: &mut Vec<u8>, n: usize) {
+    write_u64(out, n as u64);
+}
+
+fn write_int(out: &mut Vec<u8>, n: i64) {
+    write_u64(out, ((n << 1) ^ (n >> 63)) as u64);
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Number of arrays and dictionaries being read.
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], IonError> {
+        let bytes = self
+            .pos
+            .checked_add(n)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| IonError::Encoding("unexpected end of input".to_string()))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, IonError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], IonError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn bool(&mut self) -> Result<bool, IonError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(IonError::Encoding(format!("invalid boolean {b}"))),
+        }
+    }
+
+    fn u64(&mut self) -> Result<u64, IonError> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            n |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(IonError::Encoding("varint too long".to_string()))
+    }
+
+    fn len(&mut self) -> Result<usize, IonError> {
+        usize::try_from(self.u64()?)
+            .map_err(|_| IonError::Encoding("length out of range".to_string()))
+    }
+
+    fn int(&mut self) -> Result<i64, IonError> {
+        let n = self.u64()?;
+        Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+    }
+
+    fn str(&mut self) -> Result<&'a str, IonError> {
+        let len = self.len()?;
+        str::from_utf8(self.take(len)?).map_err(IonError::InvalidUtf8)
+    }
+
+    fn string(&mut self) -> Result<String, IonError> {
+        self.str().map(String::from)
+    }
+
+    fn section(&mut self) -> Result<Section, IonError> {
+        let mut section = Section::with_capacity(0);
+        section.dictionary = self.dictionary()?;
+
+        for _ in 0..self.len()? {
+            section.rows.push(self.row()?);
+        }
+
+        section.attributes = self.dictionary()?;
+
+        section.span = match self.bool()? {
+            true => Some((self.len()?, self.len()?)),
+            false => None,
+        };
+
+        for _ in 0..self.len()? {
+            let key = self.string()?;
+            section.entry_comments.insert(key, self.string()?);
+        }
+
+        for _ in 0..self.len()? {
+            let index = self.len()?;
+            section.row_comments.insert(index, self.string()?);
+        }
+
+        Ok(section)
+    }
+
+    fn dictionary(&mut self) -> Result<Dictionary, IonError> {
+        let mut dictionary = Dictionary::new();
+        for _ in 0..self.len()? {
+            let key = self.string()?;
+            dictionary.insert(key, self.value()?);
+        }
+        Ok(dictionary)
+    }
+
+    fn row(&mut self) -> Result<Row, IonError> {
+        let len = self.len()?;
+        // Every value takes at least a byte, which bounds the allocation for broken lengths.
+        let mut row = Row::with_capacity(len.min(self.bytes.len() - self.pos));
+        for _ in 0..len {
+            row.push(self.value()?);
+        }
+        Ok(row)
+    }
+
+    fn value(&mut self) -> Result<Value, IonError> {
+        let value = match self.byte()? {
+            STRING => Value::String(self.string()?),
+            INTEGER => Value::Integer(self.int()?),
+            #[cfg(feature = "i128")]
+            BIG_INT => Value::BigInt(i128::from_le_bytes(self.array()?)),
+            FLOAT => Value::Float(f64::from_le_bytes(self.array()?)),
+            BOOLEAN => Value::Boolean(self.bool()?),
+            #[cfg(feature = "datetime")]
+            DATE_TIME => {
+                let year = in_range(self.u64()?)?;
+                let [month, day, hour, minute, second] = self.array()?;
+                let nanosecond = in_range(self.u64()?)?;
+                let offset_minutes = in_range(self.int()?)?;
+                Value::DateTime(DateTime {
+                    year,
+                    month,
+                    day,
+                    hour,
+                    minute,
+                    second,
+                    nanosecond,
+                    offset_minutes,
+                })
+            }
+            ARRAY => Value::Array(self.nested(Self::row)?),
+            DICTIONARY => Value::Dictionary(self.nested(Self::dictionary)?),
+            RANGE => Value::Range {
+                start: self.int()?,
+                end: self.int()?,
+                inclusive: self.bool()?,
+            },
+            RAW => Value::Raw(self.string()?),
+            tag => return Err(IonError::Encoding(format!("unknown value tag {tag}"))),
+        };
+        Ok(value)
+    }
+
+    /// Reads a value nested in another one with `read`, failing beyond `MAX_DEPTH`.
+    fn nested<T>(&mut self, read: fn(&mut Self) -> Result<T, IonError>) -> Result<T, IonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(IonError::Encoding("nested too deep".to_string()));
+        }
+
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+}
+
+#[cfg(feature = "datetime")]
+fn in_range<T: TryFrom<U>, U>(n: U) -> Result<T, IonError> {
+    T::try_from(n).map_err(|_| IonError::Encoding("integer out of range".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Ion, IonError, Parser, Value};
+    use alloc::string::ToString;
+
+    #[test]
+    fn round_trips_everything_compared() {
+        let raw = "# preamble\n\n[FOO](version = 2)\nkey = -42 # answer\nlist = [1.5, true, { a = \"\" }]\n| a | b |\n|---|---|\n| 1 | 2 | # row\n[BAR]\nrange = 3..=7\n";
+        let parser = Parser::new(raw)
+            .with_preamble(true)
+            .with_spans(true)
+            .with_trailing_comments(true)
+            .with_ranges(true);
+        let mut ion = Ion::from_parser(parser).unwrap();
+        ion.get_mut("BAR")
+            .unwrap()
+            .dictionary
+            .insert("raw".to_string(), Value::Raw("~?".to_string()));
+
+        let bytes = ion.to_bytes();
+        assert_eq!(ion, Ion::from_bytes(&bytes).unwrap());
+        assert_eq!(
+            Ion::new_empty(),
+            Ion::from_bytes(&Ion::new_empty().to_bytes()).unwrap()
+        );
+
+        for len in 0..bytes.len() {
+            assert!(Ion::from_bytes(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth| {
+            let mut bytes = Ion::new_empty().to_bytes();
+            bytes.truncate(bytes.len() - 1);
+            // One `root` section whose dictionary holds `a`, an array nested `depth` times.
+            bytes.extend_from_slice(&[1, 4, b'r', b'o', b'o', b't', 1, 1, b'a']);
+            for _ in 0..depth {
+                bytes.extend_from_slice(&[6, 1]);
+            }
+            bytes.extend_from_slice(&[4, 1, 0, 0, 0, 0, 0]);
+            Ion::from_bytes(&bytes)
+        };
+
+        assert!(nested(128).is_ok());
+        match nested(100_000) {
+            Err(IonError::Encoding(reason)) => assert_eq!("nested too deep", reason),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = Ion::new_empty().to_bytes();
+        bytes[5] = b'x';
+
+        match Ion::from_bytes(&bytes) {
+            Err(IonError::Encoding(reason)) => assert!(reason.starts_with("written by version x")),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
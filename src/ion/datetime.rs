@@ -0,0 +1,315 @@
+use crate::IonError;
+use alloc::format;
+use core::fmt;
+use core::str::FromStr;
+
+/// A timestamp with an offset from UTC, as written in RFC 3339,
+/// e.g. `2024-06-01T12:30:00Z` or `2024-06-01T12:30:00.5+02:00`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    /// Up to `60`, for leap seconds.
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes, `0` is written as `Z`.
+    pub offset_minutes: i16,
+}
+
+/// What `lex` recognized at the start of the input, with its length in bytes.
+pub(crate) enum Lexeme {
+    /// A plain `YYYY-MM-DD` date.
+    Date(usize),
+    DateTime(DateTime, usize),
+}
+
+/// Recognizes a date or a timestamp at the start of `s`, which must not be
+/// directly followed by further letters or digits.
+pub(crate) fn lex(s: &str) -> Option<Lexeme> {
+    let mut cur = Cursor(s.as_bytes(), 0);
+
+    let year = cur.digits(4)? as u16;
+    cur.eat(b'-')?;
+    let month = cur.digits(2)? as u8;
+    cur.eat(b'-')?;
+    let day = cur.digits(2)? as u8;
+
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    if cur.eat(b'T').or_else(|| cur.eat(b't')).is_none() {
+        return cur.at_boundary().then_some(Lexeme::Date(cur.1));
+    }
+
+    let hour = cur.digits(2)? as u8;
+    cur.eat(b':')?;
+    let minute = cur.digits(2)? as u8;
+    cur.eat(b':')?;
+    let second = cur.digits(2)? as u8;
+
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut nanosecond = 0;
+    if cur.eat(b'.').is_some() {
+        let start = cur.1;
+        while matches!(cur.peek(), Some(b) if b.is_ascii_digit()) {
+            let digit = u32::from(cur.0[cur.1] - b'0');
+            if cur.1 - start < 9 {
+                nanosecond = nanosecond * 10 + digit;
+            }
+            cur.1 += 1;
+        }
+
+        match cur.1 - start {
+            0 => return None,
+            n if n < 9 => nanosecond *= 10u32.pow((9 - n) as u32),
+            _ => {}
+        }
+    }
+
+    let offset_minutes = match cur.peek()? {
+        b'Z' | b'z' => {
+            cur.1 += 1;
+            0
+        }
+        sign @ (b'+' | b'-') => {
+            cur.1 += 1;
+            let hours = cur.digits(2)?;
+            cur.eat(b':')?;
+            let minutes = cur.digits(2)?;
+
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+
+            let offset = (hours * 60 + minutes) as i16;
+            match sign {
+                b'-' => -offset,
+                _ => offset,
+            }
+        }
+        _ => return None,
+    };
+
+    let datetime = DateTime {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        nanosecond,
+        offset_minutes,
+    };
+
+    cur.at_boundary()
+        .then_some(Lexeme::DateTime(datetime, cur.1))
+}
+
+impl DateTime {
+    /// Seconds since the Unix epoch, ignoring leap seconds, and the nanoseconds on top.
+    pub(crate) fn unix_time(&self) -> (i64, u32) {
+        // Days from 1970-01-01 to the date in the proleptic Gregorian calendar,
+        // counting years from March so that the leap day ends a year.
+        let (y, m) = match self.month {
+            1 | 2 => (i64::from(self.year) - 1, i64::from(self.month) + 9),
+            _ => (i64::from(self.year), i64::from(self.month) - 3),
+        };
+        let era = y.div_euclid(400);
+        let year_of_era = y - era * 400;
+        let day_of_year = (153 * m + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+
+        let seconds = days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+            - i64::from(self.offset_minutes) * 60;
+
+        (seconds, self.nanosecond)
+    }
+}
+
+struct Cursor<'a>(&'a [u8], usize);
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.0.get(self.1).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> Option<()> {
+        match self.peek() == Some(b) {
+            true => {
+                self.1 += 1;
+                Some(())
+            }
+            false => None,
+        }
+    }
+
+    fn digits(&mut self, n: usize) -> Option<u32> {
+        let digits = self.0.get(self.1..self.1 + n)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        self.1 += n;
+        Some(
+            digits
+                .iter()
+                .fold(0, |acc, b| acc * 10 + u32::from(b - b'0')),
+        )
+    }
+
+    fn at_boundary(&self) -> bool {
+        !matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric())
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    let is_leap_year = matches!((year % 4, year % 100, year % 400), (0, 1.., _) | (_, _, 0));
+
+    match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = IonError;
+
+    fn from_str(s: &str) -> Result<Self, IonError> {
+        match lex(s) {
+            Some(Lexeme::DateTime(datetime, len)) if len == s.len() => Ok(datetime),
+            _ => Err(IonError::ParseError),
+        }
+    }
+}
+
+/// Writes the canonical RFC 3339 form: an uppercase `T`, the fraction without
+/// trailing zeros and a `Z` for UTC.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+
+        match self.offset_minutes {
+            0 => f.write_str("Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DateTime, Ion, Value};
+    use alloc::format;
+    use alloc::string::ToString;
+    use core::cmp::Ordering;
+
+    #[test]
+    fn timestamps_and_dates_coexist() {
+        let ion: Ion = r#"
+            [FOO]
+            created = 2024-06-01T12:30:00Z
+            updated = 2024-06-01T12:30:00.250+02:00
+            day = 2024-06-01
+            name = "2024-06-01T12:30:00Z"
+        "#
+        .parse()
+        .unwrap();
+        let foo = ion.get("FOO").unwrap();
+
+        let created = foo.get("created").and_then(Value::as_datetime).unwrap();
+        assert_eq!((2024, 6, 1), (created.year, created.month, created.day));
+        assert_eq!(
+            (12, 30, 0, 0),
+            (
+                created.hour,
+                created.minute,
+                created.second,
+                created.offset_minutes
+            )
+        );
+
+        let updated = foo.get("updated").and_then(Value::as_datetime).unwrap();
+        assert_eq!(250_000_000, updated.nanosecond);
+        assert_eq!(120, updated.offset_minutes);
+
+        assert_eq!(Some("2024-06-01"), foo.get("day").and_then(Value::as_str));
+        assert_eq!(
+            Some("2024-06-01T12:30:00Z"),
+            foo.get("name").and_then(Value::as_str)
+        );
+    }
+
+    #[test]
+    fn display_is_canonical() {
+        let datetime: DateTime = "2024-06-01t12:30:00.250000-05:30".parse().unwrap();
+        assert_eq!("2024-06-01T12:30:00.25-05:30", datetime.to_string());
+
+        let datetime: DateTime = "2024-06-01T12:30:00+00:00".parse().unwrap();
+        assert_eq!("2024-06-01T12:30:00Z", datetime.to_string());
+
+        let value = Value::DateTime(datetime);
+        let ion: Ion = format!("[FOO]\nkey = {value:#}").parse().unwrap();
+        assert_eq!(Some(&value), ion.get("FOO").unwrap().get("key"));
+    }
+
+    #[test]
+    fn timestamps_are_ordered_as_instants() {
+        let value = |s: &str| Value::DateTime(s.parse().unwrap());
+        let earlier = value("2024-06-01T12:00:00+02:00");
+        let later = value("2024-06-01T11:00:00Z");
+
+        assert_eq!(Ordering::Less, earlier.total_cmp(&later));
+        assert_eq!(
+            Ordering::Less,
+            value("1999-12-31T23:59:59.5Z").total_cmp(&value("2000-01-01T00:00:00Z"))
+        );
+        assert_eq!(
+            (0, 0),
+            "1970-01-01T01:00:00+01:00"
+                .parse::<DateTime>()
+                .unwrap()
+                .unix_time()
+        );
+    }
+
+    #[test]
+    fn invalid_timestamps_are_rejected() {
+        for s in [
+            "2024-02-30T12:30:00Z",
+            "2024-06-01T24:00:00Z",
+            "2024-06-01T12:30:00",
+            "2024-06-01T12:30Z",
+            "2024-06-01T12:30:00.Z",
+            "2024-06-01T12:30:00Zx",
+        ] {
+            assert!(s.parse::<DateTime>().is_err(), "{s}");
+        }
+
+        assert!("2024-02-29T00:00:00Z".parse::<DateTime>().is_ok());
+    }
+}
@@ -1,10 +1,18 @@
-use crate::{Ion, Section, Value};
-use std::fmt::{self, Write};
+use crate::{Element, Ion, Row, Section, Value};
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::{self, Write};
 
 impl fmt::Display for Ion {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        if let Some(preamble) = self.preamble() {
+            for line in preamble.split('\n') {
+                f.write_fmt(format_args!("#{line}\n"))?;
+            }
+        }
+
         for (name, section) in &self.sections {
-            f.write_fmt(format_args!("[{name}]\n"))?;
+            f.write_fmt(format_args!("{}\n", SectionHeader(name, section)))?;
             section.fmt(f)?;
             f.write_str("\n")?;
         }
@@ -13,67 +21,124 @@ impl fmt::Display for Ion {
     }
 }
 
+impl Ion {
+    /// Writes the document in `.ion` syntax, such that parsing the result with
+    /// `FromStr` yields a document equal to `self`.
+    ///
+    /// The guarantee holds when the document is representable in `.ion` syntax:
+    ///
+    /// - section names contain neither `]` nor line breaks, and dictionary keys
+    ///   consist of ASCII letters, digits, `_` and `-`,
+    /// - floats are finite,
+    /// - there are no `Value::Range`, read back only by `Parser::with_ranges`, and no
+    ///   `Value::Raw`, whose text isn't checked to be valid,
+    /// - table cells are strings without control characters other than `\n` and `\t`,
+    /// - spans are not recorded and the preamble, read back only by `Parser::with_preamble`,
+    ///   is empty,
+    /// - there is at least one section, as an empty document reads back with an empty
+    ///   `root` section.
+    ///
+    /// Floats are written in their shortest form reading back to the same value.
+    pub fn to_ion_string(&self) -> String {
+        self.to_string()
+    }
+}
+
 impl fmt::Display for Section {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         for (k, v) in &self.dictionary {
-            f.write_fmt(format_args!("{k} = {v:#}\n"))?;
+            f.write_fmt(format_args!("{k} = {v:#}"))?;
+            fmt_trailing_comment(self.entry_comments.get(k), f)?;
         }
 
-        for row in &self.rows {
-            for cell in row {
-                fmt::Display::fmt(&format!("| {cell} "), f)?;
-            }
-            f.write_str("|\n")?;
+        for (i, row) in self.rows.iter().enumerate() {
+            fmt_row(row, f)?;
+            fmt_trailing_comment(self.row_comments.get(&i), f)?;
         }
 
         Ok(())
     }
 }
 
+/// Displays `[name]`, followed by the section attributes if there are any.
+pub(crate) struct SectionHeader<'a>(pub &'a str, pub &'a Section);
+
+impl fmt::Display for SectionHeader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.write_fmt(format_args!("[{}]", self.0))?;
+
+        if self.1.attributes.is_empty() {
+            return Ok(());
+        }
+
+        f.write_char('(')?;
+        for (i, (k, v)) in self.1.attributes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_fmt(format_args!("{k} = {v:#}"))?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Element::Section(name) => f.write_fmt(format_args!("[{name}]")),
+            Element::Row(row) => fmt_row(row, f),
+            Element::Entry(k, v) => f.write_fmt(format_args!("{k} = {v:#}")),
+            Element::SectionEnd(name) => f.write_fmt(format_args!("[/{name}]")),
+            Element::TrailingComment(c) => f.write_fmt(format_args!("#{c}")),
+            Element::Comment(c) => {
+                f.write_char('#')?;
+                f.write_str(c.trim_end_matches(&['\n', '\r'][..]))
+            }
+        }
+    }
+}
+
+/// Ends an entry or row line, with its comment if there is one.
+fn fmt_trailing_comment(comment: Option<&String>, f: &mut fmt::Formatter) -> fmt::Result {
+    match comment {
+        Some(comment) => f.write_fmt(format_args!(" #{comment}\n")),
+        None => f.write_str("\n"),
+    }
+}
+
+fn fmt_row(row: &Row, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    for cell in row {
+        fmt::Display::fmt(&format!("| {cell} "), f)?;
+    }
+    f.write_str("|")
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
-            Value::String(v) => {
-                if f.alternate() {
-                    f.write_char('"')?;
-                    for c in v.chars() {
-                        match c {
-                            '\\' => f.write_str("\\\\")?,
-                            '\n' => f.write_str("\\n")?,
-                            '\"' => f.write_str("\\\"")?,
-                            _ => f.write_char(c)?,
-                        }
-                    }
-                    f.write_char('"')?;
-                } else {
-                    let mut escaping = false;
-                    for c in v.chars() {
-                        match (escaping, c) {
-                            (false, '\\') => {
-                                escaping = true;
-                                f.write_char('\\')?;
-                                continue;
-                            }
-                            (false, '\n') => f.write_str("\\n")?,
-                            (false, '\t') => f.write_str("\\t")?,
-                            (false, '|') => f.write_str("\\|")?,
-
-                            (true, '\\') => f.write_char('\\')?,
-                            (true, 'n') => f.write_str("\\n")?,
-                            (true, 't') => f.write_str("\\t")?,
-                            (true, '|') => f.write_str("\\|")?,
-
-                            (_, c) => f.write_char(c)?,
-                        }
-                        escaping = false;
-                    }
-                }
-                Ok(())
+            Value::String(v) if f.alternate() => {
+                f.write_char('"')?;
+                f.write_str(&Value::escape_for_string(v))?;
+                f.write_char('"')
             }
+            Value::String(v) => f.write_str(&Value::escape_for_cell(v)),
 
             Value::Integer(v) => v.fmt(f),
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) => v.fmt(f),
+            Value::Float(v) if f.alternate() && v.is_finite() => {
+                // Without a decimal point the value would be read back as an integer.
+                let s = format!("{v}");
+                f.write_str(&s)?;
+                match s.contains('.') {
+                    true => Ok(()),
+                    false => f.write_str(".0"),
+                }
+            }
             Value::Float(v) => v.fmt(f),
             Value::Boolean(v) => v.fmt(f),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(v) => v.fmt(f),
 
             Value::Array(v) => {
                 f.write_str("[ ")?;
@@ -93,6 +158,16 @@ impl fmt::Display for Value {
                 f.write_str(" ]")
             }
 
+            Value::Range {
+                start,
+                end,
+                inclusive,
+            } => match inclusive {
+                true => write!(f, "{start}..={end}"),
+                false => write!(f, "{start}..{end}"),
+            },
+            Value::Raw(v) => f.write_str(v),
+
             Value::Dictionary(d) => {
                 f.write_str("{ ")?;
 
@@ -116,3 +191,315 @@ impl fmt::Display for Value {
         }
     }
 }
+
+/// Presentation options for `Value::to_display_string`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayOpts {
+    /// Separates groups of three digits of integers with `,`, e.g. `1,234,567`.
+    pub group_thousands: bool,
+}
+
+impl Value {
+    /// Escapes `s` for a table cell, i.e. `\\`, line breaks, tabs and `|`,
+    /// as written by `Display`. A leading `#` or space and trailing whitespace
+    /// are escaped too, as they would start a comment or be trimmed.
+    pub fn escape_for_cell(s: &str) -> String {
+        let last = s.char_indices().last().map(|(i, _)| i);
+        let mut out = String::with_capacity(s.len());
+
+        for (i, c) in s.char_indices() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\t' => out.push_str("\\t"),
+                '|' => out.push_str("\\|"),
+                '#' | ' ' if i == 0 => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                c if Some(i) == last && c.is_whitespace() => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                c => out.push(c),
+            }
+        }
+
+        out
+    }
+
+    /// Escapes `s` for a quoted string, i.e. `\\`, line breaks and `"`, without
+    /// adding the quotes, as written by the alternate `Display`.
+    pub fn escape_for_string(s: &str) -> String {
+        escape(s, |c| match c {
+            '\\' => Some("\\\\"),
+            '\n' => Some("\\n"),
+            '"' => Some("\\\""),
+            _ => None,
+        })
+    }
+
+    /// Formats the value for presentation, unlike `Display` the result is not
+    /// meant to be parsed back.
+    pub fn to_display_string(&self, opts: DisplayOpts) -> String {
+        match self {
+            Value::Integer(v) if opts.group_thousands => group_thousands(&v.to_string()),
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) if opts.group_thousands => group_thousands(&v.to_string()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+fn escape(s: &str, escaped: impl Fn(char) -> Option<&'static str>) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match escaped(c) {
+            Some(escape) => out.push_str(escape),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+fn group_thousands(digits: &str) -> String {
+    let (sign, digits) = match digits.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", digits),
+    };
+
+    let mut out = String::from(sign);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_value, Dictionary, DisplayOpts, Ion, Parser, Row, Value};
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    fn parse_cell(s: &str) -> Option<Value> {
+        let raw = format!("| {} |", Value::new_string(s));
+        let mut section = Parser::new(&raw).read()?.remove("root")?;
+        section.rows.pop()?.pop()
+    }
+
+    fn parse_entry(s: &str) -> Option<Value> {
+        let raw = format!("key = {:#}", Value::new_string(s));
+        Parser::new(&raw)
+            .read()?
+            .remove("root")?
+            .dictionary
+            .remove("key")
+    }
+
+    #[test]
+    fn cell_escaping_round_trips() {
+        for s in [
+            "a\\nb", "a\nb", "a\\\nb", "a\\\\b", "a\\", "a\\|b", "a|b", "a\tb", "a\\\"b",
+        ] {
+            assert_eq!(Some(Value::new_string(s)), parse_cell(s), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn entry_escaping_round_trips() {
+        for s in ["a\\nb", "a\nb", "a\\", "a\\\"b", "a\"b", "a|b"] {
+            assert_eq!(Some(Value::new_string(s)), parse_entry(s), "{s:?}");
+        }
+    }
+
+    #[quickcheck]
+    fn any_cell_round_trips(s: String) -> TestResult {
+        // Cells are trimmed, may not start a comment and reject control characters.
+        if s.trim() != s
+            || s.starts_with('#')
+            || s.chars().any(|c| c.is_control() && c != '\n' && c != '\t')
+        {
+            return TestResult::discard();
+        }
+
+        TestResult::from_bool(Some(Value::new_string(&s)) == parse_cell(&s))
+    }
+
+    #[test]
+    fn section_attributes_round_trip() {
+        let ion: Ion = "[FOO](type=table, version=2)\n| 1 |\n[BAR]\n"
+            .parse()
+            .unwrap();
+
+        let displayed = ion.to_string();
+
+        assert_eq!(
+            "[BAR]\n\n[FOO](type = \"table\", version = 2)\n| 1 |\n\n",
+            displayed
+        );
+        assert_eq!(
+            ion.get("FOO").unwrap().attributes,
+            displayed
+                .parse::<Ion>()
+                .unwrap()
+                .get("FOO")
+                .unwrap()
+                .attributes
+        );
+    }
+
+    #[test]
+    fn preamble_round_trip() {
+        let raw = "# Hotel definitions\n# generated\n[FOO]\nkey = 1\n";
+        let ion = Ion::from_parser(Parser::new(raw).with_preamble(true)).unwrap();
+
+        let displayed = ion.to_string();
+
+        assert_eq!(
+            "# Hotel definitions\n# generated\n[FOO]\nkey = 1\n\n",
+            displayed
+        );
+        assert_eq!(
+            ion.preamble(),
+            Ion::from_parser(Parser::new(&displayed).with_preamble(true))
+                .unwrap()
+                .preamble()
+        );
+    }
+
+    #[test]
+    fn floats_keep_decimal_point() {
+        assert_eq!("1.0", format!("{:#}", Value::Float(1.0)));
+        assert_eq!("-0.5", format!("{:#}", Value::Float(-0.5)));
+        assert_eq!("1", format!("{}", Value::Float(1.0)));
+        assert_eq!(Some(Value::Float(-3.0)), parse_value("-3.0").ok());
+    }
+
+    #[test]
+    fn trailing_comments_round_trip() {
+        let raw = "[FOO]\nport = 80 # http\nhost = \"localhost\"\n| a | b | # first\n| c | d |\n";
+
+        let ion = Ion::from_parser(Parser::new(raw).with_trailing_comments(true)).unwrap();
+        let foo = ion.get("FOO").unwrap();
+        assert_eq!(
+            Some(" http"),
+            foo.entry_comments.get("port").map(String::as_str)
+        );
+        assert_eq!(Some(" first"), foo.row_comments.get(&0).map(String::as_str));
+        assert_eq!(1, foo.row_comments.len());
+
+        let written = ion.to_string();
+        assert!(written.contains("port = 80 # http\n"), "{written}");
+        assert!(written.contains("| a | b | # first\n"), "{written}");
+        assert_eq!(
+            ion,
+            Ion::from_parser(Parser::new(&written).with_trailing_comments(true)).unwrap()
+        );
+
+        let ion: Ion = raw.parse().unwrap();
+        assert!(ion.get("FOO").unwrap().entry_comments.is_empty());
+        assert!(ion.get("FOO").unwrap().row_comments.is_empty());
+    }
+
+    #[test]
+    fn escape_helpers() {
+        let cell = Value::escape_for_cell("a | b\tc");
+        assert_eq!(r"a \| b\tc", cell);
+
+        let raw = format!(
+            "| {cell} |\nkey = \"{}\"",
+            Value::escape_for_string("say \"hi\"\n")
+        );
+        let section = Parser::new(&raw).read().unwrap().remove("root").unwrap();
+        assert_eq!(vec![vec![Value::new_string("a | b\tc")]], section.rows);
+        assert_eq!(
+            Some("say \"hi\"\n"),
+            section.get("key").and_then(Value::as_str)
+        );
+
+        for s in ["# not a comment", " padded ", " ", "a\u{3000}", "a # b"] {
+            assert_eq!(Some(Value::new_string(s)), parse_cell(s), "{s:?}");
+        }
+        assert_eq!(r"\# a\ ", Value::escape_for_cell("# a "));
+    }
+
+    #[test]
+    fn display_string_groups_thousands() {
+        let grouped = DisplayOpts {
+            group_thousands: true,
+        };
+
+        assert_eq!(
+            "1,234,567",
+            Value::Integer(1234567).to_display_string(grouped)
+        );
+        assert_eq!(
+            "-123,456",
+            Value::Integer(-123456).to_display_string(grouped)
+        );
+        assert_eq!("999", Value::Integer(999).to_display_string(grouped));
+        assert_eq!(
+            "1234567",
+            Value::Integer(1234567).to_display_string(DisplayOpts::default())
+        );
+        assert_eq!("1234567", Value::Integer(1234567).to_string());
+    }
+
+    #[quickcheck]
+    fn to_ion_string_round_trips(
+        entries: Vec<(u8, i64, f64, String, bool)>,
+        cells: Vec<String>,
+    ) -> TestResult {
+        let mut ion = Ion::new(Default::default());
+
+        for (i, (section, int, float, string, boolean)) in entries.into_iter().enumerate() {
+            let float = if float.is_finite() { float } else { 0.5 };
+            let section = ion.section_entry(format!("S{}", section % 4));
+            let mut entry =
+                |key: &str, value| section.dictionary.insert(format!("{key}{i}"), value);
+
+            entry("int", Value::Integer(int));
+            entry("float", Value::Float(float));
+            entry("string", Value::String(string.clone()));
+            entry("bool", Value::Boolean(boolean));
+            entry(
+                "array",
+                Value::Array(vec![
+                    Value::Integer(int),
+                    Value::Float(float),
+                    Value::String(string),
+                ]),
+            );
+            let mut dictionary = Dictionary::new();
+            dictionary.insert("a".into(), Value::Array(vec![Value::Boolean(boolean)]));
+            entry("dict", Value::Dictionary(dictionary));
+        }
+
+        let row: Row = cells
+            .iter()
+            .map(|cell| {
+                cell.chars()
+                    .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
+                    .collect::<String>()
+            })
+            .map(Value::String)
+            .collect();
+        if !row.is_empty() {
+            ion.section_entry("S0").rows.push(row);
+        }
+
+        if ion.iter().count() == 0 {
+            return TestResult::discard();
+        }
+
+        TestResult::from_bool(ion == ion.to_ion_string().parse().unwrap())
+    }
+
+    #[quickcheck]
+    fn any_entry_round_trips(s: String) -> bool {
+        Some(Value::new_string(&s)) == parse_entry(&s)
+    }
+}
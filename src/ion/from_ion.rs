@@ -1,6 +1,8 @@
 use crate::ion::Value;
-use std::num::ParseIntError;
-use std::str::ParseBoolError;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use core::num::ParseIntError;
+use core::str::ParseBoolError;
 
 pub trait FromIon<T>
 where
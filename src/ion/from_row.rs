@@ -10,6 +10,15 @@ where
     fn from_str_iter<'a, I>(row: I) -> Result<Self, Self::Err>
     where
         I: Iterator<Item = &'a Value>;
+
+    /// Builds `Self` from a content row of a table with the given header row,
+    /// allowing implementors to look up cells by column name instead of position.
+    ///
+    /// The default implementation ignores the header and delegates to `from_str_iter`.
+    fn from_row_with_header(header: &Row, row: &Row) -> Result<Self, Self::Err> {
+        let _ = header;
+        Self::from_str_iter(row.iter())
+    }
 }
 
 pub trait ParseRow
@@ -32,6 +41,7 @@ impl ParseRow for Row {
 #[cfg(test)]
 mod tests {
     use crate::ion::{FromRow, Value};
+    use crate::{ion, Ion, Row};
 
     macro_rules! parse_next {
         ($row:expr, $err:expr) => {{
@@ -79,4 +89,92 @@ mod tests {
             foo
         );
     }
+
+    #[derive(Debug, PartialEq)]
+    struct Bar {
+        code: String,
+        amount: u32,
+    }
+
+    impl FromRow for Bar {
+        type Err = &'static str;
+
+        fn from_str_iter<'a, I: Iterator<Item = &'a Value>>(mut row: I) -> Result<Self, Self::Err> {
+            Ok(Bar {
+                code: parse_next!(row, "code"),
+                amount: parse_next!(row, "amount"),
+            })
+        }
+
+        fn from_row_with_header(header: &Row, row: &Row) -> Result<Self, Self::Err> {
+            let cell = |name: &'static str| {
+                header
+                    .iter()
+                    .position(|h| h.as_str() == Some(name))
+                    .and_then(|idx| row.get(idx))
+                    .ok_or(name)
+            };
+
+            Ok(Bar {
+                code: cell("code")?.parse().map_err(|_| "code")?,
+                amount: cell("amount")?.parse().map_err(|_| "amount")?,
+            })
+        }
+    }
+
+    #[test]
+    fn from_row_with_header() {
+        let first = ion!(
+            r#"
+            [BAR]
+            | code | amount |
+            |------|--------|
+            | RO   | 10     |
+            | BB   | 20     |
+            "#
+        );
+
+        let second = ion!(
+            r#"
+            [BAR]
+            | amount | code |
+            |--------|------|
+            | 10     | RO   |
+            | 20     | BB   |
+            "#
+        );
+
+        let expected = vec![
+            Bar {
+                code: "RO".to_owned(),
+                amount: 10,
+            },
+            Bar {
+                code: "BB".to_owned(),
+                amount: 20,
+            },
+        ];
+
+        assert_eq!(expected, first.get("BAR").unwrap().parse_rows().unwrap());
+        assert_eq!(expected, second.get("BAR").unwrap().parse_rows().unwrap());
+    }
+
+    #[test]
+    fn from_row_without_header() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            | 1 | foo |
+            "#
+        );
+
+        let rows: Vec<Foo> = ion.get("FOO").unwrap().parse_rows().unwrap();
+        assert_eq!(
+            vec![Foo {
+                foo: 1,
+                bar: "foo".to_owned(),
+            }],
+            rows
+        );
+    }
 }
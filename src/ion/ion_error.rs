@@ -1,18 +1,93 @@
 use crate::parser::ParserError;
-use std::{error, fmt};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::Utf8Error;
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum IonError {
     MissingSection(String),
     MissingValue(String),
     ParseError,
     ParserErrors(Vec<ParserError>),
+    /// A value was of another type than the operation requires, see `Value::type_str`.
+    UnexpectedType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The input is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// Decoding the input failed, holds the reason, see `Ion::from_utf16_reader`.
+    Encoding(String),
+    /// Reading the input failed, holds the I/O error message.
+    Io(String),
+    /// Converting to another format failed, holds the reason.
+    Conversion(String),
+    /// The document has sections outside of an allowlist, see `Ion::validate_no_unknown_sections`.
+    UnknownSections(Vec<String>),
+    /// A section's `when` entry is not a valid condition, see `Ion::resolve_conditionals`.
+    InvalidCondition {
+        section: String,
+        expression: String,
+    },
+    /// The input is larger than allowed, see `Parser::with_max_input_size`.
+    InputTooLarge {
+        size: usize,
+        max: usize,
+    },
+    /// An arithmetic operation overflowed, see `Value::checked_add`.
+    Overflow,
+    /// Wraps an error with the path of the file it occurred in.
+    InFile {
+        path: String,
+        source: Box<IonError>,
+    },
 }
 
-impl error::Error for IonError {
+impl IonError {
+    /// Returns `true` for `IonError::MissingSection`, also when wrapped in `IonError::InFile`.
+    pub fn is_missing_section(&self) -> bool {
+        match self {
+            IonError::MissingSection(_) => true,
+            IonError::InFile { source, .. } => source.is_missing_section(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` for `IonError::MissingValue`, also when wrapped in `IonError::InFile`.
+    pub fn is_missing_value(&self) -> bool {
+        match self {
+            IonError::MissingValue(_) => true,
+            IonError::InFile { source, .. } => source.is_missing_value(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` for `IonError::ParseError` and `IonError::ParserErrors`,
+    /// also when wrapped in `IonError::InFile`.
+    pub fn is_parse_error(&self) -> bool {
+        match self {
+            IonError::ParseError | IonError::ParserErrors(_) => true,
+            IonError::InFile { source, .. } => source.is_parse_error(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IonError {
     fn description(&self) -> &str {
         "IonError"
     }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IonError::InFile { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for IonError {
@@ -20,3 +95,107 @@ impl fmt::Display for IonError {
         fmt::Debug::fmt(self, f)
     }
 }
+
+/// An error together with the input line it occurred on, see `Ion::parse_with_context`.
+#[derive(Clone, Debug)]
+pub struct IonErrorWithContext {
+    pub error: IonError,
+    /// 1-based line of the first parser error.
+    pub line: usize,
+    /// 1-based column of the first parser error, counted in chars.
+    pub column: usize,
+    /// The input line containing the first parser error.
+    pub snippet: String,
+}
+
+impl IonErrorWithContext {
+    pub(crate) fn new(error: IonError, input: &str) -> Self {
+        let lo = match &error {
+            IonError::ParserErrors(errors) => errors.first().map_or(0, |e| e.lo),
+            _ => 0,
+        };
+        let lo = lo.min(input.len());
+
+        let (line, column) = line_column(input, lo);
+        let line_start = input[..lo].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[lo..].find('\n').map_or(input.len(), |i| lo + i);
+
+        IonErrorWithContext {
+            error,
+            line,
+            column,
+            snippet: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+        }
+    }
+}
+
+/// Returns the 1-based line and column, counted in chars, of byte offset `lo` in `input`.
+pub(crate) fn line_column(input: &str, lo: usize) -> (usize, usize) {
+    let lo = lo.min(input.len());
+    let line_start = input[..lo].rfind('\n').map_or(0, |i| i + 1);
+
+    (
+        input[..lo].matches('\n').count() + 1,
+        input[line_start..lo].chars().count() + 1,
+    )
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IonErrorWithContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for IonErrorWithContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.error)?;
+        writeln!(f, " --> line {}, column {}", self.line, self.column)?;
+        writeln!(f, "  | {}", self.snippet)?;
+        write!(f, "  | {:>width$}", "^", width = self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Ion, IonError};
+
+    #[test]
+    fn predicates() {
+        let missing_section = IonError::MissingSection("FOO".into());
+        assert!(missing_section.is_missing_section());
+        assert!(!missing_section.is_missing_value());
+        assert!(!missing_section.is_parse_error());
+
+        let missing_value = IonError::MissingValue("key".into());
+        assert!(!missing_value.is_missing_section());
+        assert!(missing_value.is_missing_value());
+        assert!(!missing_value.is_parse_error());
+
+        assert!(IonError::ParseError.is_parse_error());
+        assert!(IonError::ParserErrors(vec![]).is_parse_error());
+        assert!(!IonError::Io("denied".into()).is_parse_error());
+
+        let in_file = IonError::InFile {
+            path: "foo.ion".into(),
+            source: Box::new(missing_value),
+        };
+        assert!(in_file.is_missing_value());
+        assert!(!in_file.is_missing_section());
+    }
+
+    #[test]
+    fn parse_with_context() {
+        let err = Ion::parse_with_context("[FOO]\nkey = 1\n  bad line\nother = 2\n").unwrap_err();
+
+        assert!(err.error.is_parse_error());
+        assert_eq!(3, err.line);
+        assert_eq!("  bad line", err.snippet);
+        assert!(err.to_string().contains("  |   bad line\n"));
+
+        let err = Ion::try_from("[FOO]\nkey = 1\n  bad line\n").unwrap_err();
+        assert_eq!(3, err.line);
+    }
+}
@@ -0,0 +1,176 @@
+use super::display::SectionHeader;
+use crate::{Ion, Section};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// Formatting options for `Ion::write_pretty`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// Pads table cells so that all columns of a section line up,
+    /// the header separator row is filled with `-` to the column width.
+    pub align_columns: bool,
+    /// Pads keys so that the `=` of all entries in a section line up.
+    pub align_keys: bool,
+    /// Ends the output with a single newline, otherwise the last line is left unterminated.
+    pub trailing_newline: bool,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            align_columns: true,
+            align_keys: false,
+            trailing_newline: true,
+        }
+    }
+}
+
+impl Ion {
+    /// Writes the document in a deterministic layout suited for version control.
+    ///
+    /// Sections are written in order, separated by a single blank line.
+    pub fn write_pretty<W: Write>(&self, w: &mut W, opts: PrettyOptions) -> fmt::Result {
+        let mut out = String::new();
+
+        for (i, (name, section)) in self.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+
+            out.write_fmt(format_args!("{}\n", SectionHeader(name, section)))?;
+            write_section(&mut out, section, opts)?;
+        }
+
+        if !opts.trailing_newline {
+            out.truncate(out.trim_end_matches('\n').len());
+        }
+
+        w.write_str(&out)
+    }
+}
+
+fn write_section(out: &mut String, section: &Section, opts: PrettyOptions) -> fmt::Result {
+    let key_width = match opts.align_keys {
+        true => section.dictionary.keys().map(|k| k.chars().count()).max(),
+        false => None,
+    };
+
+    for (k, v) in &section.dictionary {
+        out.push_str(k);
+        if let Some(width) = key_width {
+            pad(out, ' ', width - k.chars().count());
+        }
+        out.write_fmt(format_args!(" = {v:#}\n"))?;
+    }
+
+    let rows: Vec<Vec<String>> = section
+        .rows
+        .iter()
+        .map(|row| row.iter().map(ToString::to_string).collect())
+        .collect();
+
+    let widths = match opts.align_columns {
+        true => column_widths(&rows),
+        false => Vec::new(),
+    };
+
+    let separator = section.header().map(|_| 1);
+
+    for (i, row) in rows.iter().enumerate() {
+        if opts.align_columns && Some(i) == separator {
+            for width in &widths {
+                out.push('|');
+                pad(out, '-', width + 2);
+            }
+            out.push_str("|\n");
+            continue;
+        }
+
+        for (col, cell) in row.iter().enumerate() {
+            out.push_str("| ");
+            out.push_str(cell);
+            out.push(' ');
+            if let Some(width) = widths.get(col) {
+                pad(out, ' ', width - cell.chars().count());
+            }
+        }
+        out.push_str("|\n");
+    }
+
+    Ok(())
+}
+
+fn column_widths(rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths = Vec::new();
+
+    for row in rows {
+        if widths.len() < row.len() {
+            widths.resize(row.len(), 0);
+        }
+
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    widths
+}
+
+fn pad(out: &mut String, ch: char, n: usize) {
+    for _ in 0..n {
+        out.push(ch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Ion, PrettyOptions};
+
+    const RAW: &str = r#"
+[CONTRACT]
+id = "HOTEL001"
+currency = "EUR"
+
+[BOARD]
+| code | description |
+|---|---|
+| RO | Room Only |
+| BB | Bed & Breakfast |
+"#;
+
+    #[test]
+    fn aligns_table_columns() {
+        let ion: Ion = RAW.parse().unwrap();
+        let mut out = String::new();
+        ion.write_pretty(&mut out, PrettyOptions::default())
+            .unwrap();
+
+        let expected = "\
+[BOARD]
+| code | description     |
+|------|-----------------|
+| RO   | Room Only       |
+| BB   | Bed & Breakfast |
+
+[CONTRACT]
+currency = \"EUR\"
+id = \"HOTEL001\"
+";
+        assert_eq!(expected, out);
+    }
+
+    #[test]
+    fn aligns_keys_without_trailing_newline() {
+        let ion: Ion = "[A]\nid = 1\ncurrency = 2".parse().unwrap();
+        let opts = PrettyOptions {
+            align_keys: true,
+            trailing_newline: false,
+            ..PrettyOptions::default()
+        };
+        let mut out = String::new();
+        ion.write_pretty(&mut out, opts).unwrap();
+
+        assert_eq!("[A]\ncurrency = 2\nid       = 1", out);
+    }
+}
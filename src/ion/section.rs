@@ -1,13 +1,38 @@
-use crate::{Dictionary, FromIon, IonError, Row, Value};
-use std::vec;
+use crate::{Dictionary, FromIon, FromRow, IonError, Row, Value};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Section {
     pub dictionary: Dictionary,
     pub rows: Vec<Row>,
+    /// Attributes given after the section header, as in `[FOO](type=table, version=2)`.
+    pub attributes: Dictionary,
+    /// Byte range of the section in the parsed input, see `Parser::with_spans`.
+    pub span: Option<(usize, usize)>,
+    /// Comments at the end of entry lines by key, see `Parser::with_trailing_comments`.
+    pub entry_comments: BTreeMap<String, String>,
+    /// Comments at the end of row lines by index into `rows`. The row methods of
+    /// `Section` keep them with their rows, editing `rows` directly doesn't.
+    pub row_comments: BTreeMap<usize, String>,
 }
 
 impl Section {
+    /// Name of the entry declaring the columns of a table without header row,
+    /// see `declared_columns`.
+    pub const COLUMNS_KEY: &'static str = "cols";
+
+    /// Name of the section holding the content before the first header, also
+    /// named by an empty header `[]`.
+    pub const DEFAULT_NAME: &'static str = "root";
+
+    /// Name of the entry making the section conditional, see `Ion::resolve_conditionals`.
+    pub const WHEN_KEY: &'static str = "when";
+
     pub fn new() -> Section {
         Self::with_capacity(1)
     }
@@ -16,6 +41,10 @@ impl Section {
         Self {
             dictionary: Dictionary::new(),
             rows: Vec::with_capacity(n),
+            attributes: Dictionary::new(),
+            span: None,
+            entry_comments: BTreeMap::new(),
+            row_comments: BTreeMap::new(),
         }
     }
 
@@ -31,29 +60,406 @@ impl Section {
         self.dictionary.get_mut(name)
     }
 
+    /// Iterates over the dictionary entries in key order.
+    pub fn iter_entries(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.dictionary.iter()
+    }
+
+    /// Iterates over the dictionary entries in key order, with mutable values.
+    pub fn iter_entries_mut(&mut self) -> impl Iterator<Item = (&String, &mut Value)> {
+        self.dictionary.iter_mut()
+    }
+
+    pub fn attribute(&self, key: &str) -> Option<&Value> {
+        self.attributes.get(key)
+    }
+
     pub fn fetch(&self, key: &str) -> Result<&Value, IonError> {
         self.get(key)
             .ok_or_else(|| IonError::MissingValue(key.to_owned()))
     }
 
+    /// Returns the header row of a table, i.e. the first row when it's followed
+    /// by a `|---|` separator row.
+    pub fn header(&self) -> Option<&Row> {
+        match self.header_len() {
+            0 => None,
+            _ => self.rows.first(),
+        }
+    }
+
+    /// Returns the column names declared by the `cols = [ "name", "age" ]` entry,
+    /// see `declared_columns_at`.
+    ///
+    /// Tables without header row use these columns in `rows_as_dicts`, `parse_rows`
+    /// and `to_jsonl`.
+    pub fn declared_columns(&self) -> Option<Vec<String>> {
+        self.declared_columns_at(Self::COLUMNS_KEY)
+    }
+
+    /// Returns the column names declared by the entry `key`, or `None` when there is
+    /// no such entry or it's not an array of strings.
+    ///
+    /// Only the `cols` entry is used by the table accessors, columns declared under
+    /// another key have to be applied by the caller.
+    pub fn declared_columns_at(&self, key: &str) -> Option<Vec<String>> {
+        self.get(key)?
+            .as_array()?
+            .iter()
+            .map(|cell| cell.as_str().map(str::to_owned))
+            .collect()
+    }
+
+    /// Returns the content rows as dictionaries keyed by column name, or `None` when
+    /// the table has neither a header row nor `declared_columns`.
+    ///
+    /// Cells beyond the named columns are keyed by their column index.
+    pub fn rows_as_dicts(&self) -> Option<Vec<Dictionary>> {
+        let header = self.column_header()?;
+
+        let dicts = self
+            .rows_without_header()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(i, cell)| match header.get(i) {
+                        Some(name) => (name.to_string(), cell.clone()),
+                        None => (i.to_string(), cell.clone()),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(dicts)
+    }
+
+    /// Appends `rows` after the existing rows.
+    pub fn extend_rows(&mut self, rows: impl IntoIterator<Item = Row>) {
+        self.rows.extend(rows);
+    }
+
+    /// Appends the rows of `other`. The header of `other` and its separator row are
+    /// only kept when `self` has no rows yet, so they never end up amid the content.
+    pub fn extend_from_section(&mut self, other: &Section) {
+        let skipped = match self.rows.is_empty() {
+            true => 0,
+            false => other.header_len(),
+        };
+        let offset = self.rows.len();
+
+        self.rows.extend_from_slice(&other.rows[skipped..]);
+        self.row_comments.extend(
+            other
+                .row_comments
+                .range(skipped..)
+                .map(|(i, comment)| (offset + i - skipped, comment.clone())),
+        );
+    }
+
+    /// Moves the dictionary out of the section, dropping the rows.
+    pub fn into_dictionary(self) -> Dictionary {
+        self.dictionary
+    }
+
+    /// Moves all rows out of the section, dropping the dictionary.
+    ///
+    /// Unlike `into_iter`, the header and its separator row are included.
+    pub fn into_rows(self) -> Vec<Row> {
+        self.rows
+    }
+
+    /// Turns a key/value table into a dictionary, mapping the first cell of each
+    /// content row to its second cell. Further cells are ignored, and a later row
+    /// overrides an earlier one with the same key.
+    ///
+    /// Returns `None` when a content row has fewer than two cells.
+    pub fn pivot(&self) -> Option<Dictionary> {
+        self.rows_without_header()
+            .iter()
+            .map(|row| match row.as_slice() {
+                [Value::String(key), value, ..] => Some((key.clone(), value.clone())),
+                [key, value, ..] => Some((key.to_string(), value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the distinct numbers of cells of the content rows, in ascending order.
+    pub fn row_widths(&self) -> Vec<usize> {
+        self.rows_without_header()
+            .iter()
+            .map(Vec::len)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns `true` when all content rows have the same number of cells, as is
+    /// the case for a section without rows. The header isn't compared.
+    pub fn is_rectangular(&self) -> bool {
+        self.row_widths().len() <= 1
+    }
+
+    /// Returns the header row, falling back to a row of the `declared_columns`.
+    pub(crate) fn column_header(&self) -> Option<Cow<'_, Row>> {
+        match self.header() {
+            Some(header) => Some(Cow::Borrowed(header)),
+            None => self
+                .declared_columns()
+                .map(|columns| Cow::Owned(columns.into_iter().map(Value::String).collect())),
+        }
+    }
+
     pub fn rows_without_header(&self) -> &[Row] {
-        if self.rows.len() > 1 {
-            let row = &self.rows[1];
-
-            if row.first().map_or(false, |v| match v {
-                Value::String(s) => !s.is_empty() && s.chars().all(|c| c == '-'),
-                _ => false,
-            }) {
-                return &self.rows[2..];
+        &self.rows[self.header_len()..]
+    }
+
+    /// Returns the `i`-th content row, the header rows are not counted.
+    pub fn get_row(&self, i: usize) -> Option<&Row> {
+        self.rows_without_header().get(i)
+    }
+
+    /// Returns the cell at column `col` of the `row`-th content row, the header rows are not counted.
+    pub fn get_cell(&self, row: usize, col: usize) -> Option<&Value> {
+        self.get_row(row).and_then(|row| row.get(col))
+    }
+
+    /// Retains only the content rows for which the predicate returns `true`.
+    ///
+    /// When the section has a header, the header and its separator row are always kept
+    /// and are never passed to the predicate.
+    pub fn retain_rows(&mut self, mut f: impl FnMut(&Row) -> bool) {
+        let header_len = self.header_len();
+        let keep = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| i < header_len || f(row))
+            .collect();
+
+        self.retain_by_mask(keep);
+    }
+
+    /// Removes consecutive duplicate content rows, keeping the first of each run.
+    ///
+    /// The header and its separator row are never compared nor removed.
+    pub fn dedup_rows(&mut self) {
+        let keep = self.dedup_mask(|a, b| self.rows[a] == self.rows[b]);
+        self.retain_by_mask(keep);
+    }
+
+    /// Removes consecutive content rows resolving to the same key, keeping the first of each run.
+    ///
+    /// The header and its separator row are never compared nor removed.
+    pub fn dedup_rows_by_key<K: PartialEq>(&mut self, key: impl FnMut(&Row) -> K) {
+        let keys: Vec<K> = self.rows.iter().map(key).collect();
+        let keep = self.dedup_mask(|a, b| keys[a] == keys[b]);
+        self.retain_by_mask(keep);
+    }
+
+    /// Removes every content row equal to an earlier one, keeping the remaining rows in order.
+    ///
+    /// The header and its separator row are never compared nor removed.
+    #[cfg(feature = "std")]
+    pub fn dedup_rows_global(&mut self) {
+        use core::hash::{Hash, Hasher};
+        use std::collections::hash_map::{DefaultHasher, HashMap};
+
+        let header_len = self.header_len();
+        // Rows are bucketed by hash and compared with `PartialEq`, as `Value` isn't `Eq`.
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut keep = Vec::with_capacity(self.rows.len());
+
+        for (i, row) in self.rows.iter().enumerate() {
+            if i >= header_len {
+                let mut hasher = DefaultHasher::new();
+                row.hash(&mut hasher);
+                let bucket = buckets.entry(hasher.finish()).or_default();
+
+                if bucket.iter().any(|&kept| self.rows[kept] == *row) {
+                    keep.push(false);
+                    continue;
+                }
+
+                bucket.push(i);
+            }
+
+            keep.push(true);
+        }
+
+        self.retain_by_mask(keep);
+    }
+
+    /// Sorts the content rows with `compare`, keeping their trailing comments with them.
+    ///
+    /// The header and its separator row stay in place. The sort is stable, so rows
+    /// comparing equal keep their order.
+    pub fn sort_rows_by(&mut self, mut compare: impl FnMut(&Row, &Row) -> Ordering) {
+        let header_len = self.header_len();
+        let mut content: Vec<(Row, Option<String>)> = self
+            .rows
+            .drain(header_len..)
+            .enumerate()
+            .map(|(i, row)| (row, self.row_comments.remove(&(header_len + i))))
+            .collect();
+        content.sort_by(|(a, _), (b, _)| compare(a, b));
+
+        for (row, comment) in content {
+            if let Some(comment) = comment {
+                self.row_comments.insert(self.rows.len(), comment);
+            }
+            self.rows.push(row);
+        }
+    }
+
+    /// Overlays the entries of `other` onto the dictionary, leaving the rows untouched.
+    ///
+    /// Keys already present are replaced only when `overwrite` is `true`.
+    pub fn merge_dictionary(&mut self, other: &Dictionary, overwrite: bool) {
+        for (key, value) in other {
+            if overwrite || !self.dictionary.contains_key(key) {
+                self.dictionary.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    /// Removes the entries equal to their counterpart in `defaults`, the inverse of
+    /// `merge_dictionary`, so that only the overrides remain.
+    pub fn strip_defaults(&mut self, defaults: &Dictionary) {
+        self.dictionary
+            .retain(|key, value| defaults.get(key) != Some(value));
+    }
+
+    /// Returns a copy of the section with every content row transformed by `f`.
+    ///
+    /// The header and its separator row are copied unchanged and never passed to `f`.
+    pub fn map_rows(&self, f: impl Fn(&Row) -> Row) -> Section {
+        let header_len = self.header_len();
+
+        let mut section = Section::with_capacity(self.rows.len());
+        section.dictionary = self.dictionary.clone();
+        section.attributes = self.attributes.clone();
+        section.span = self.span;
+        section.entry_comments = self.entry_comments.clone();
+        section.row_comments = self.row_comments.clone();
+        section.rows.extend_from_slice(&self.rows[..header_len]);
+        section.rows.extend(self.rows[header_len..].iter().map(f));
+        section
+    }
+
+    /// Swaps columns `a` and `b` in every row, the header and its separator row included.
+    ///
+    /// Rows too short to have both columns are left as they are.
+    pub fn swap_columns(&mut self, a: usize, b: usize) {
+        for row in &mut self.rows {
+            if a < row.len() && b < row.len() {
+                row.swap(a, b);
             }
         }
+    }
 
-        &self.rows
+    /// Removes column `col` from every row, the header and its separator row included.
+    ///
+    /// Rows too short to have the column are left as they are.
+    pub fn remove_column(&mut self, col: usize) {
+        for row in &mut self.rows {
+            if col < row.len() {
+                row.remove(col);
+            }
+        }
+    }
+
+    /// Returns a new section with the rows of `self` followed by the rows of `other`,
+    /// and the dictionary and attributes of `self` with those of `other` layered on top.
+    ///
+    /// Rows are appended as by `extend_from_section`. The span of `self` is kept.
+    pub fn concat(&self, other: &Section) -> Section {
+        let mut section = self.clone();
+        section.merge_dictionary(&other.dictionary, true);
+        section.attributes.extend(
+            other
+                .attributes
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        for key in other.dictionary.keys() {
+            match other.entry_comments.get(key) {
+                Some(comment) => section.entry_comments.insert(key.clone(), comment.clone()),
+                None => section.entry_comments.remove(key),
+            };
+        }
+        section.extend_from_section(other);
+        section
     }
 
     pub fn parse<F: FromIon<Section>>(&self) -> Result<F, F::Err> {
         F::from_ion(self)
     }
+
+    /// Parses every content row, passing the header to `FromRow::from_row_with_header`
+    /// when the table has one, or else `declared_columns`.
+    pub fn parse_rows<F: FromRow>(&self) -> Result<Vec<F>, F::Err> {
+        let rows = self.rows_without_header().iter();
+
+        match self.column_header() {
+            Some(header) => rows
+                .map(|row| F::from_row_with_header(&header, row))
+                .collect(),
+            None => rows.map(|row| F::from_str_iter(row.iter())).collect(),
+        }
+    }
+
+    /// Flags the rows to keep when removing consecutive content rows for which `same`
+    /// returns `true`, given the indices of the row and of the last one kept.
+    fn dedup_mask(&self, mut same: impl FnMut(usize, usize) -> bool) -> Vec<bool> {
+        let header_len = self.header_len();
+        let mut last = None;
+        let mut keep = Vec::with_capacity(self.rows.len());
+
+        for i in 0..self.rows.len() {
+            let duplicate = match last {
+                Some(last) => i >= header_len && same(i, last),
+                None => false,
+            };
+            if !duplicate && i >= header_len {
+                last = Some(i);
+            }
+            keep.push(!duplicate);
+        }
+
+        keep
+    }
+
+    /// Retains the rows whose entry in `keep` is `true`, moving the row comments along.
+    fn retain_by_mask(&mut self, keep: Vec<bool>) {
+        let mut new_index = Vec::with_capacity(keep.len());
+        let mut kept = 0;
+        for &k in &keep {
+            new_index.push(kept);
+            kept += usize::from(k);
+        }
+
+        self.row_comments = core::mem::take(&mut self.row_comments)
+            .into_iter()
+            .filter_map(|(i, comment)| match keep.get(i) {
+                Some(true) => Some((new_index[i], comment)),
+                Some(false) => None,
+                None => Some((i + kept - keep.len(), comment)),
+            })
+            .collect();
+
+        let mut keep = keep.into_iter();
+        self.rows.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// Number of leading rows forming the header: `2` (header and separator) or `0`.
+    fn header_len(&self) -> usize {
+        match self.rows.get(1).and_then(|row| row.first()) {
+            Some(Value::String(s)) if !s.is_empty() && s.chars().all(|c| c == '-') => 2,
+            _ => 0,
+        }
+    }
 }
 
 pub struct IntoIter<T> {
@@ -76,7 +482,7 @@ impl<T> Iterator for IntoIter<T> {
 
 impl<'a> IntoIterator for &'a Section {
     type Item = &'a Row;
-    type IntoIter = std::slice::Iter<'a, Row>;
+    type IntoIter = core::slice::Iter<'a, Row>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.rows_without_header().iter()
@@ -128,7 +534,9 @@ mod tests {
     use regex::Regex;
 
     fn is_input_string_invalid(s: &str) -> bool {
-        Regex::new("[\n \t\r|\\\\]|^-+$").unwrap().is_match(s)
+        Regex::new("[\n \t\r|\\\\\\p{Cc}]|^-+$")
+            .unwrap()
+            .is_match(s)
     }
 
     mod into_iter {
@@ -314,6 +722,725 @@ mod tests {
         }
     }
 
+    mod retain_rows {
+        use super::*;
+        use crate::Value;
+
+        #[test]
+        fn it_keeps_header_and_matching_rows() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                | num | name  |
+                |-----|-------|
+                | 1   | one   |
+                | 2   | two   |
+                | 3   | three |
+                | 4   | four  |
+                "#
+            );
+
+            let section = ion.get_mut("FOO").unwrap();
+            section.retain_rows(
+                |row| matches!(row[0].as_str().map(str::parse::<i64>), Some(Ok(n)) if n % 2 == 0),
+            );
+
+            assert_eq!(4, section.rows.len());
+            assert_eq!(Some("num"), section.rows[0][0].as_str());
+            assert_eq!(Some("-----"), section.rows[1][0].as_str());
+
+            let rows = section.rows_without_header();
+            assert_eq!(2, rows.len());
+            assert_eq!(Value::String("two".to_owned()), rows[0][1]);
+            assert_eq!(Value::String("four".to_owned()), rows[1][1]);
+        }
+
+        #[test]
+        fn it_applies_to_all_rows_without_header() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                | 1 | one |
+                | 2 | two |
+                "#
+            );
+
+            let section = ion.get_mut("FOO").unwrap();
+            section.retain_rows(|row| row[0].as_str() == Some("2"));
+
+            assert_eq!(1, section.rows.len());
+            assert_eq!(Some("two"), section.rows[0][1].as_str());
+        }
+    }
+
+    mod dedup_rows {
+        use super::*;
+        use crate::Value;
+
+        const RAW: &str = r#"
+            [FOO]
+            | num | name |
+            |-----|------|
+            | 1   | one  |
+            | 1   | one  |
+            | 2   | two  |
+            | 1   | one  |
+            | 3   | one  |
+        "#;
+
+        fn names(section: &Section) -> Vec<&str> {
+            section
+                .rows_without_header()
+                .iter()
+                .map(|row| row[0].as_str().unwrap())
+                .collect()
+        }
+
+        #[test]
+        fn it_removes_consecutive_duplicates() {
+            let mut ion = ion!(RAW);
+            let section = ion.get_mut("FOO").unwrap();
+
+            section.dedup_rows();
+
+            assert_eq!(Some("num"), section.rows[0][0].as_str());
+            assert_eq!(Some("-----"), section.rows[1][0].as_str());
+            assert_eq!(vec!["1", "2", "1", "3"], names(section));
+        }
+
+        #[test]
+        fn it_removes_consecutive_duplicates_by_key() {
+            let mut ion = ion!(RAW);
+            let section = ion.get_mut("FOO").unwrap();
+
+            section.dedup_rows_by_key(|row| row[1].clone());
+
+            assert_eq!(Some("num"), section.rows[0][0].as_str());
+            assert_eq!(vec!["1", "2", "1"], names(section));
+        }
+
+        #[test]
+        fn it_removes_all_duplicates_globally() {
+            let mut ion = ion!(RAW);
+            let section = ion.get_mut("FOO").unwrap();
+
+            section.dedup_rows_global();
+
+            assert_eq!(Some("num"), section.rows[0][0].as_str());
+            assert_eq!(Some("-----"), section.rows[1][0].as_str());
+            assert_eq!(vec!["1", "2", "3"], names(section));
+        }
+
+        #[test]
+        fn it_treats_signed_zeros_as_duplicates() {
+            let mut section = Section::new();
+            section.rows.push(vec![Value::Float(0.0)]);
+            section.rows.push(vec![Value::Float(-0.0)]);
+
+            section.dedup_rows_global();
+
+            assert_eq!(1, section.rows.len());
+        }
+    }
+
+    mod get_row {
+        use super::*;
+        use crate::Value;
+
+        #[test]
+        fn it_skips_header_rows() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | num | name |
+                |-----|------|
+                | 1   | one  |
+                | 2   | two  |
+                "#
+            );
+            let section = ion.get("FOO").unwrap();
+
+            assert_eq!(Some("1"), section.get_row(0).unwrap()[0].as_str());
+            assert!(section.get_row(2).is_none());
+            assert_eq!(Some("two"), section.get_cell(1, 1).and_then(Value::as_str));
+            assert!(section.get_cell(1, 2).is_none());
+            assert!(section.get_cell(2, 0).is_none());
+        }
+
+        #[test]
+        fn it_indexes_all_rows_without_header() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | 1 | one |
+                | 2 | two |
+                "#
+            );
+            let section = ion.get("FOO").unwrap();
+
+            assert_eq!(Some("one"), section.get_cell(0, 1).and_then(Value::as_str));
+        }
+    }
+
+    mod concat {
+        use super::*;
+        use crate::Value;
+
+        #[test]
+        fn it_keeps_a_single_header() {
+            let ion = ion!(
+                r#"
+                [PAGE1]
+                page = 1
+                currency = "EUR"
+                | num | name |
+                |-----|------|
+                | 1   | one  |
+                | 2   | two  |
+
+                [PAGE2]
+                page = 2
+                | num | name  |
+                |-----|-------|
+                | 3   | three |
+                "#
+            );
+            let page1 = ion.get("PAGE1").unwrap();
+            let page2 = ion.get("PAGE2").unwrap();
+
+            let section = page1.concat(page2);
+
+            assert_eq!(page1.header(), section.header());
+            assert_eq!(5, section.rows.len());
+            let names: Vec<_> = section
+                .rows_without_header()
+                .iter()
+                .map(|row| row[1].as_str().unwrap())
+                .collect();
+            assert_eq!(vec!["one", "two", "three"], names);
+            assert_eq!(Some(2), section.get("page").and_then(Value::as_integer));
+            assert_eq!(Some("EUR"), section.get("currency").and_then(Value::as_str));
+        }
+
+        #[test]
+        fn it_keeps_all_rows_without_header() {
+            let ion = ion!(
+                r#"
+                [PAGE1]
+                | 1 | one |
+                [PAGE2]
+                | 2 | two |
+                "#
+            );
+
+            let section = ion.get("PAGE1").unwrap().concat(ion.get("PAGE2").unwrap());
+
+            assert_eq!(2, section.rows.len());
+            assert_eq!(None, section.header());
+        }
+
+        #[test]
+        fn it_skips_the_other_header_after_rows_without_header() {
+            let ion = ion!(
+                r#"
+                [PAGE1](page = 1)
+                | 1 | one |
+                [PAGE2]
+                | num | name |
+                |-----|------|
+                | 2   | two  |
+                "#
+            );
+
+            let section = ion.get("PAGE1").unwrap().concat(ion.get("PAGE2").unwrap());
+
+            assert_eq!(2, section.rows.len());
+            assert_eq!(None, section.header());
+            assert_eq!(Some(&Value::Integer(1)), section.attribute("page"));
+        }
+    }
+
+    mod row_comments {
+        use super::*;
+        use crate::Parser;
+
+        const RAW: &str = "[FOO]\n| num | name |\n|-----|------|\n| 2 | two | # second\n| 1 | one | # first\n| 2 | two |\n| 3 | three | # third\n";
+
+        fn parse() -> Section {
+            let parser = Parser::new(RAW).with_trailing_comments(true);
+            Ion::from_parser(parser).unwrap().remove("FOO").unwrap()
+        }
+
+        fn comments(section: &Section) -> Vec<(&str, &str)> {
+            section
+                .row_comments
+                .iter()
+                .map(|(&i, comment)| (section.rows[i][1].as_str().unwrap(), comment.as_str()))
+                .collect()
+        }
+
+        #[test]
+        fn then_they_follow_retained_rows() {
+            let mut section = parse();
+            section.retain_rows(|row| row[0].as_str() != Some("1"));
+
+            assert_eq!(
+                vec![("two", " second"), ("three", " third")],
+                comments(&section)
+            );
+        }
+
+        #[test]
+        fn then_they_follow_deduplicated_rows() {
+            let mut section = parse();
+            section.dedup_rows_by_key(|row| row[0].as_str() == Some("3"));
+
+            assert_eq!(4, section.rows.len());
+            assert_eq!(
+                vec![("two", " second"), ("three", " third")],
+                comments(&section)
+            );
+        }
+
+        #[test]
+        fn then_they_follow_sorted_rows() {
+            let mut section = parse();
+            section.sort_rows_by(|a, b| a[0].as_str().cmp(&b[0].as_str()));
+
+            let names: Vec<_> = section
+                .rows_without_header()
+                .iter()
+                .map(|row| row[1].as_str().unwrap())
+                .collect();
+            assert_eq!(vec!["one", "two", "two", "three"], names);
+            assert_eq!(
+                vec![("one", " first"), ("two", " second"), ("three", " third")],
+                comments(&section)
+            );
+        }
+
+        #[test]
+        fn then_they_follow_concatenated_rows() {
+            let section = parse();
+            let twice = section.concat(&section);
+
+            assert_eq!(10, twice.rows.len());
+            assert_eq!(6, twice.row_comments.len());
+            assert_eq!(Some(&" first".to_owned()), twice.row_comments.get(&7));
+            assert_eq!(
+                section.row_comments,
+                section.map_rows(Clone::clone).row_comments
+            );
+        }
+    }
+
+    mod merge_dictionary {
+        use super::*;
+        use crate::Value;
+
+        const RAW: &str = r#"
+            [FOO]
+            currency = "EUR"
+            | 1 | one |
+
+            [DEFAULTS]
+            currency = "USD"
+            language = "en"
+        "#;
+
+        #[test]
+        fn it_overwrites_existing_keys() {
+            let mut ion = ion!(RAW);
+            let defaults = ion.get("DEFAULTS").unwrap().dictionary.clone();
+
+            let section = ion.get_mut("FOO").unwrap();
+            section.merge_dictionary(&defaults, true);
+
+            assert_eq!(Some("USD"), section.get("currency").and_then(Value::as_str));
+            assert_eq!(Some("en"), section.get("language").and_then(Value::as_str));
+            assert_eq!(1, section.rows.len());
+        }
+
+        #[test]
+        fn it_keeps_existing_keys_without_overwrite() {
+            let mut ion = ion!(RAW);
+            let defaults = ion.get("DEFAULTS").unwrap().dictionary.clone();
+
+            let section = ion.get_mut("FOO").unwrap();
+            section.merge_dictionary(&defaults, false);
+
+            assert_eq!(Some("EUR"), section.get("currency").and_then(Value::as_str));
+            assert_eq!(Some("en"), section.get("language").and_then(Value::as_str));
+            assert_eq!(1, section.rows.len());
+        }
+    }
+
+    mod strip_defaults {
+        use super::*;
+        use crate::Value;
+
+        #[test]
+        fn it_keeps_only_overrides() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                currency = "EUR"
+                language = "en"
+                nights = 7
+                | 1 | one |
+
+                [DEFAULTS]
+                currency = "USD"
+                language = "en"
+                nights = 7
+            "#
+            );
+            let defaults = ion.get("DEFAULTS").unwrap().dictionary.clone();
+
+            let section = ion.get_mut("FOO").unwrap();
+            section.strip_defaults(&defaults);
+
+            assert_eq!(1, section.dictionary.len());
+            assert_eq!(Some("EUR"), section.get("currency").and_then(Value::as_str));
+            assert_eq!(1, section.rows.len());
+        }
+    }
+
+    mod map_rows {
+        use super::*;
+        use crate::Value;
+
+        #[test]
+        fn it_maps_content_rows_only() {
+            let ion = ion!("[FOO]\nkey = 1\n| name |\n|------|\n| one  |\n| two  |");
+            let section = ion.get("FOO").unwrap();
+
+            let upper = section.map_rows(|row| {
+                row.iter()
+                    .map(|cell| Value::new_string(&cell.to_string().to_uppercase()))
+                    .collect()
+            });
+
+            assert_eq!(Some(&vec![Value::new_string("name")]), upper.header());
+            assert_eq!(
+                vec![
+                    vec![Value::new_string("ONE")],
+                    vec![Value::new_string("TWO")]
+                ],
+                upper.rows_without_header()
+            );
+            assert_eq!(section.dictionary, upper.dictionary);
+            assert_eq!(Some("one"), section.get_cell(0, 0).and_then(Value::as_str));
+        }
+    }
+
+    mod extend_rows {
+        use super::*;
+        use crate::Value;
+
+        const RAW: &str = r#"
+            [FOO]
+            | name | age |
+            |------|-----|
+            | Ann  | 42  |
+
+            [BAR]
+            | name | age |
+            |------|-----|
+            | Bob  | 7   |
+            | Cid  | 9   |
+        "#;
+
+        fn names(section: &Section) -> Vec<&str> {
+            section
+                .rows_without_header()
+                .iter()
+                .filter_map(|row| row[0].as_str())
+                .collect()
+        }
+
+        #[test]
+        fn it_appends_rows_in_order() {
+            let mut ion = ion!(RAW);
+            let foo = ion.get_mut("FOO").unwrap();
+
+            foo.extend_rows(vec![
+                vec![Value::new_string("Bob"), Value::new_string("7")],
+                vec![Value::new_string("Cid"), Value::new_string("9")],
+            ]);
+
+            assert_eq!(5, foo.rows.len());
+            assert_eq!(vec!["Ann", "Bob", "Cid"], names(foo));
+        }
+
+        #[test]
+        fn it_skips_the_other_header() {
+            let ion = ion!(RAW);
+            let mut foo = ion.get("FOO").unwrap().clone();
+
+            foo.extend_from_section(ion.get("BAR").unwrap());
+
+            assert_eq!(5, foo.rows.len());
+            assert_eq!(vec!["Ann", "Bob", "Cid"], names(&foo));
+        }
+
+        #[test]
+        fn it_keeps_the_other_header_without_own_header() {
+            let ion = ion!(RAW);
+            let mut section = Section::new();
+
+            section.extend_from_section(ion.get("BAR").unwrap());
+
+            assert_eq!(
+                Some("name"),
+                section.header().and_then(|header| header[0].as_str())
+            );
+            assert_eq!(vec!["Bob", "Cid"], names(&section));
+        }
+
+        #[test]
+        fn it_skips_the_other_header_after_rows_without_header() {
+            let ion = ion!(RAW);
+            let mut section = Section::new();
+            section.extend_rows(vec![vec![Value::new_string("Ann")]]);
+
+            section.extend_from_section(ion.get("BAR").unwrap());
+
+            assert_eq!(3, section.rows.len());
+            assert_eq!(None, section.header());
+        }
+    }
+
+    mod into_dictionary {
+        use super::*;
+        use crate::Value;
+
+        const RAW: &str = "[FOO]\nkey = 1\n| name | age |\n|------|-----|\n| Ann | 42 |";
+
+        #[test]
+        fn it_returns_entries() {
+            let dictionary = ion!(RAW).into_section("FOO").unwrap().into_dictionary();
+
+            assert_eq!(1, dictionary.len());
+            assert_eq!(Some(&Value::Integer(1)), dictionary.get("key"));
+        }
+
+        #[test]
+        fn it_returns_rows_including_header() {
+            let rows = ion!(RAW).into_section("FOO").unwrap().into_rows();
+
+            assert_eq!(3, rows.len());
+            assert_eq!(Some("name"), rows[0][0].as_str());
+            assert_eq!(Some("Ann"), rows[2][0].as_str());
+        }
+    }
+
+    mod pivot {
+        use super::*;
+        use crate::Value;
+
+        #[test]
+        fn it_maps_first_cell_to_second() {
+            let ion = ion!(
+                "[FOO]\n| key | value |\n|-----|-------|\n| RO | Room Only |\n| BB | Breakfast | x |"
+            );
+
+            let dictionary = ion.get("FOO").unwrap().pivot().unwrap();
+
+            assert_eq!(2, dictionary.len());
+            assert_eq!(Some(&Value::new_string("Room Only")), dictionary.get("RO"));
+            assert_eq!(Some(&Value::new_string("Breakfast")), dictionary.get("BB"));
+        }
+
+        #[test]
+        fn it_keeps_escaped_characters_of_keys() {
+            let ion = ion!("[FOO]\n| a\\|b | pipe |");
+
+            let dictionary = ion.get("FOO").unwrap().pivot().unwrap();
+
+            assert_eq!(Some(&Value::new_string("pipe")), dictionary.get("a|b"));
+        }
+
+        #[test]
+        fn it_returns_none_for_short_rows() {
+            let ion = ion!("[FOO]\n| RO | Room Only |\n| BB |");
+
+            assert_eq!(None, ion.get("FOO").unwrap().pivot());
+        }
+    }
+
+    mod row_widths {
+        use super::*;
+
+        #[test]
+        fn it_returns_distinct_widths_of_content_rows() {
+            let ion = ion!(
+                "[FOO]\n| a | b | c |\n|---|---|---|\n| 1 | 2 |\n| 1 | 2 | 3 | 4 |\n| 1 | 2 |"
+            );
+            let section = ion.get("FOO").unwrap();
+
+            assert_eq!(vec![2, 4], section.row_widths());
+            assert!(!section.is_rectangular());
+        }
+
+        #[test]
+        fn it_is_rectangular_for_equal_widths() {
+            let ion = ion!("[FOO]\n| 1 | 2 |\n| 3 | 4 |\n[BAR]\nkey = 1");
+
+            assert_eq!(vec![2], ion.get("FOO").unwrap().row_widths());
+            assert!(ion.get("FOO").unwrap().is_rectangular());
+            assert!(ion.get("BAR").unwrap().row_widths().is_empty());
+            assert!(ion.get("BAR").unwrap().is_rectangular());
+        }
+    }
+
+    mod columns {
+        use super::*;
+        use crate::Value;
+
+        const RAW: &str = r#"
+        [FOO]
+        | id | name | price |
+        |----|------|-------|
+        | 1  | one  | 10    |
+        | 2  | two  |
+        | 3  |
+    "#;
+
+        #[test]
+        fn it_removes_column_from_header_and_rows() {
+            let mut ion = ion!(RAW);
+            let section = ion.get_mut("FOO").unwrap();
+
+            section.remove_column(1);
+
+            assert_eq!(
+                Some(&vec![Value::new_string("id"), Value::new_string("price")]),
+                section.header()
+            );
+            assert_eq!(
+                vec![
+                    vec![Value::new_string("1"), Value::new_string("10")],
+                    vec![Value::new_string("2")],
+                    vec![Value::new_string("3")],
+                ],
+                section.rows_without_header()
+            );
+        }
+
+        #[test]
+        fn it_swaps_columns_in_header_and_rows() {
+            let mut ion = ion!(RAW);
+            let section = ion.get_mut("FOO").unwrap();
+
+            section.swap_columns(0, 2);
+
+            assert_eq!(
+                Some(&vec![
+                    Value::new_string("price"),
+                    Value::new_string("name"),
+                    Value::new_string("id")
+                ]),
+                section.header()
+            );
+            assert_eq!(
+                vec![
+                    Value::new_string("10"),
+                    Value::new_string("one"),
+                    Value::new_string("1")
+                ],
+                section.rows_without_header()[0]
+            );
+            assert_eq!(2, section.rows_without_header()[1].len());
+            assert_eq!(Value::new_string("2"), section.rows_without_header()[1][0]);
+        }
+    }
+
+    mod declared_columns {
+        use super::*;
+        use crate::{Dictionary, Value};
+
+        const RAW: &str = r#"
+            [FOO]
+            cols = [ "name", "age" ]
+            | alice | 30 |
+            | bob   | 25 | extra |
+
+            [BAR]
+            | alice | 30 |
+        "#;
+
+        #[test]
+        fn it_returns_declared_columns() {
+            let ion = ion!(RAW);
+
+            assert_eq!(
+                Some(vec!["name".to_owned(), "age".to_owned()]),
+                ion.get("FOO").unwrap().declared_columns()
+            );
+            assert_eq!(None, ion.get("BAR").unwrap().declared_columns());
+        }
+
+        #[test]
+        fn it_returns_rows_as_dicts_by_declared_columns() {
+            let ion = ion!(RAW);
+
+            let dicts = ion.get("FOO").unwrap().rows_as_dicts().unwrap();
+
+            let mut alice = Dictionary::new();
+            alice.insert("name".to_owned(), Value::new_string("alice"));
+            alice.insert("age".to_owned(), Value::new_string("30"));
+            assert_eq!(alice, dicts[0]);
+            assert_eq!(Some(&Value::new_string("extra")), dicts[1].get("2"));
+
+            assert_eq!(None, ion.get("BAR").unwrap().rows_as_dicts());
+        }
+
+        #[test]
+        fn it_prefers_header_row() {
+            let ion = ion!("[FOO]\ncols = [ \"a\" ]\n| b |\n|---|\n| 1 |");
+
+            let dicts = ion.get("FOO").unwrap().rows_as_dicts().unwrap();
+
+            assert_eq!(Some(&Value::new_string("1")), dicts[0].get("b"));
+        }
+    }
+
+    mod iter_entries {
+        use super::*;
+        use crate::Value;
+
+        const RAW: &str = r#"
+        [FOO]
+        b = 2
+        a = 1
+        | 3 |
+    "#;
+
+        #[test]
+        fn it_iterates_entries_in_key_order() {
+            let ion = ion!(RAW);
+            let section = ion.get("FOO").unwrap();
+
+            let keys: Vec<&str> = section.iter_entries().map(|(k, _)| k.as_str()).collect();
+
+            assert_eq!(vec!["a", "b"], keys);
+        }
+
+        #[test]
+        fn it_mutates_entries() {
+            let mut ion = ion!(RAW);
+            let section = ion.get_mut("FOO").unwrap();
+
+            for (_, value) in section.iter_entries_mut() {
+                *value = Value::Integer(value.as_integer().unwrap() * 10);
+            }
+
+            assert_eq!(Some(10), section.get("a").and_then(Value::as_integer));
+            assert_eq!(Some(20), section.get("b").and_then(Value::as_integer));
+        }
+    }
+
     mod without_headers {
         use super::*;
 
@@ -0,0 +1,320 @@
+use crate::{Dictionary, IonError, ParserErrorKind, Section, Value};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Integer(v) => serializer.serialize_i64(*v),
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) => serializer.serialize_i128(*v),
+            Value::Float(v) => serializer.serialize_f64(*v),
+            Value::Boolean(v) => serializer.serialize_bool(*v),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(v) => serializer.collect_str(v),
+            Value::Array(v) => v.serialize(serializer),
+            Value::Dictionary(v) => v.serialize(serializer),
+            Value::Range { .. } => serializer.collect_str(self),
+            Value::Raw(v) => serializer.serialize_str(v),
+        }
+    }
+}
+
+/// Maps `null` to an empty string, like an empty table cell. Integers beyond `i64`
+/// become `Value::BigInt` with the `i128` feature, and `Value::Float` otherwise.
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Integer(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(Value::Integer(v)),
+            #[cfg(feature = "i128")]
+            Err(_) => Ok(Value::BigInt(v.into())),
+            #[cfg(not(feature = "i128"))]
+            Err(_) => Ok(Value::Float(v as f64)),
+        }
+    }
+
+    #[cfg(feature = "i128")]
+    fn visit_i128<E>(self, v: i128) -> Result<Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(Value::Integer(v)),
+            Err(_) => Ok(Value::BigInt(v)),
+        }
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut dictionary = Dictionary::new();
+        while let Some((key, value)) = map.next_entry()? {
+            dictionary.insert(key, value);
+        }
+        Ok(Value::Dictionary(dictionary))
+    }
+}
+
+/// A content row serialized as an object keyed by the header cells.
+struct HeaderRow<'a> {
+    header: &'a [Value],
+    row: &'a [Value],
+}
+
+impl Serialize for HeaderRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.row.len()))?;
+
+        for (i, cell) in self.row.iter().enumerate() {
+            match self.header.get(i) {
+                Some(name) => map.serialize_entry(&name.to_string(), cell)?,
+                None => map.serialize_entry(&i.to_string(), cell)?,
+            }
+        }
+
+        map.end()
+    }
+}
+
+impl Section {
+    /// Emits the content rows as JSON Lines, one line per row.
+    ///
+    /// With a header or `declared_columns`, each row becomes an object keyed by the column
+    /// names, cells beyond them are keyed by their column index. Otherwise each row becomes an array.
+    pub fn to_jsonl(&self) -> Result<String, IonError> {
+        let mut out = String::new();
+        let header = self.column_header();
+
+        for row in self.rows_without_header() {
+            let line = match &header {
+                Some(header) => serde_json::to_string(&HeaderRow { header, row }),
+                None => serde_json::to_string(row),
+            };
+
+            out.push_str(&line.map_err(|e| IonError::Conversion(e.to_string()))?);
+            out.push('\n');
+        }
+
+        Ok(out)
+    }
+}
+
+impl IonError {
+    /// Describes the error as a JSON object for editor integrations, e.g.
+    /// `{"line":3,"column":17,"section":"CONTRACT","kind":"UnterminatedArray","message":"..."}`.
+    ///
+    /// For parser errors the first error is described, located by its 1-based line
+    /// and column and the section it occurred in, `root` before the first header.
+    /// Other errors have `null` locations.
+    pub fn to_diagnostic_json(&self) -> String {
+        let (kind, message, location) = match self.innermost() {
+            IonError::ParserErrors(errors) if !errors.is_empty() => {
+                let section = errors[0]
+                    .section
+                    .as_deref()
+                    .unwrap_or(Section::DEFAULT_NAME);
+                (
+                    parser_error_kind(errors[0].kind),
+                    errors[0].desc.clone(),
+                    Some((errors[0].line, errors[0].column, section)),
+                )
+            }
+            error => (error_kind(error), error.to_string(), None),
+        };
+
+        serde_json::json!({
+            "line": location.as_ref().map(|(line, _, _)| line),
+            "column": location.as_ref().map(|(_, column, _)| column),
+            "section": location.as_ref().map(|(_, _, section)| section),
+            "kind": kind,
+            "message": message,
+        })
+        .to_string()
+    }
+
+    fn innermost(&self) -> &IonError {
+        match self {
+            IonError::InFile { source, .. } => source.innermost(),
+            error => error,
+        }
+    }
+}
+
+fn parser_error_kind(kind: ParserErrorKind) -> &'static str {
+    match kind {
+        ParserErrorKind::UnterminatedArray => "UnterminatedArray",
+        ParserErrorKind::UnterminatedDictionary => "UnterminatedDictionary",
+        ParserErrorKind::InvalidValue => "InvalidValue",
+        ParserErrorKind::InvalidNumber => "InvalidNumber",
+        ParserErrorKind::InvalidRange => "InvalidRange",
+        ParserErrorKind::NestingTooDeep => "NestingTooDeep",
+        ParserErrorKind::Syntax => "Syntax",
+    }
+}
+
+fn error_kind(error: &IonError) -> &'static str {
+    match error {
+        IonError::MissingSection(_) => "MissingSection",
+        IonError::MissingValue(_) => "MissingValue",
+        IonError::ParseError | IonError::ParserErrors(_) => "Syntax",
+        IonError::UnexpectedType { .. } => "UnexpectedType",
+        IonError::InvalidUtf8(_) => "InvalidUtf8",
+        IonError::Encoding(_) => "Encoding",
+        IonError::Io(_) => "Io",
+        IonError::Conversion(_) => "Conversion",
+        IonError::UnknownSections(_) => "UnknownSections",
+        IonError::InvalidCondition { .. } => "InvalidCondition",
+        IonError::InputTooLarge { .. } => "InputTooLarge",
+        IonError::Overflow => "Overflow",
+        IonError::InFile { source, .. } => error_kind(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dictionary, Ion, IonError, Value};
+
+    #[test]
+    fn deserialize_value() {
+        let value = |json| serde_json::from_str::<Value>(json).unwrap();
+
+        assert_eq!(Value::new_string(""), value("null"));
+        assert_eq!(Value::Boolean(true), value("true"));
+        assert_eq!(Value::Integer(-3), value("-3"));
+        assert_eq!(Value::Float(2.5), value("2.5"));
+        assert_eq!(Value::new_string("foo"), value("\"foo\""));
+        assert_eq!(
+            Value::Array(vec![Value::Integer(1), Value::new_string("a")]),
+            value("[1, \"a\"]")
+        );
+
+        let mut dictionary = Dictionary::new();
+        dictionary.insert("a".to_owned(), Value::Array(vec![]));
+        assert_eq!(Value::Dictionary(dictionary), value("{\"a\": []}"));
+
+        #[cfg(not(feature = "i128"))]
+        assert_eq!(Value::Float(u64::MAX as f64), value("18446744073709551615"));
+        #[cfg(feature = "i128")]
+        assert_eq!(
+            Value::BigInt(u64::MAX.into()),
+            value("18446744073709551615")
+        );
+    }
+
+    #[test]
+    fn diagnostic_json_without_location() {
+        let error = IonError::MissingSection("FOO".to_owned());
+
+        let json: serde_json::Value = serde_json::from_str(&error.to_diagnostic_json()).unwrap();
+
+        assert_eq!(serde_json::Value::Null, json["line"]);
+        assert_eq!(serde_json::Value::Null, json["section"]);
+        assert_eq!("MissingSection", json["kind"]);
+    }
+
+    #[test]
+    fn diagnostic_json_section_of_nested_arrays() {
+        let input = "[FOO]\nlist = [\n  [1, 2],\n  [3, 4.5.6]\n]\n";
+        let error = input.parse::<Ion>().unwrap_err();
+
+        let json: serde_json::Value = serde_json::from_str(&error.to_diagnostic_json()).unwrap();
+
+        assert_eq!("FOO", json["section"]);
+        assert_eq!(4, json["line"]);
+    }
+
+    #[test]
+    fn to_jsonl_with_header() {
+        let ion: Ion = r#"
+            [BOARD]
+            | code | description |
+            |------|-------------|
+            | RO   | Room Only   |
+            | BB   | Bed & Breakfast |
+        "#
+        .parse()
+        .unwrap();
+
+        let jsonl = ion.get("BOARD").unwrap().to_jsonl().unwrap();
+
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            vec![
+                serde_json::json!({ "code": "RO", "description": "Room Only" }),
+                serde_json::json!({ "code": "BB", "description": "Bed & Breakfast" }),
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn to_jsonl_without_header() {
+        let ion: Ion = r#"
+            [BOARD]
+            | RO | Room Only |
+            | BB |
+        "#
+        .parse()
+        .unwrap();
+
+        let jsonl = ion.get("BOARD").unwrap().to_jsonl().unwrap();
+
+        assert_eq!("[\"RO\",\"Room Only\"]\n[\"BB\"]\n", jsonl);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Dictionary, Ion, IonError, Value};
+use alloc::string::{String, ToString};
+
+impl Ion {
+    /// Converts the dictionaries of all sections into a TOML document, one table per section.
+    ///
+    /// Table rows have no TOML equivalent and are dropped. Fails with
+    /// `IonError::Conversion` for values TOML can't hold, such as integers beyond `i64`.
+    pub fn to_toml_string(&self) -> Result<String, IonError> {
+        let mut document = toml::value::Table::new();
+
+        for (name, section) in self.iter() {
+            document.insert(name.clone(), to_toml_table(&section.dictionary)?);
+        }
+
+        toml::to_string(&toml::Value::Table(document))
+            .map_err(|e| IonError::Conversion(e.to_string()))
+    }
+}
+
+fn to_toml_table(dictionary: &Dictionary) -> Result<toml::Value, IonError> {
+    dictionary
+        .iter()
+        .map(|(k, v)| Ok((k.clone(), to_toml(v)?)))
+        .collect::<Result<_, _>>()
+        .map(toml::Value::Table)
+}
+
+fn to_toml(value: &Value) -> Result<toml::Value, IonError> {
+    Ok(match value {
+        Value::String(v) => toml::Value::String(v.clone()),
+        Value::Integer(v) => toml::Value::Integer(*v),
+        #[cfg(feature = "i128")]
+        Value::BigInt(v) => {
+            return Err(IonError::Conversion(format!(
+                "integer {v} does not fit into a TOML integer"
+            )));
+        }
+        Value::Float(v) => toml::Value::Float(*v),
+        Value::Boolean(v) => toml::Value::Boolean(*v),
+        #[cfg(feature = "datetime")]
+        Value::DateTime(v) => {
+            toml::Value::Datetime(v.to_string().parse().map_err(
+                |e: toml::value::DatetimeParseError| IonError::Conversion(e.to_string()),
+            )?)
+        }
+        Value::Array(v) => toml::Value::Array(v.iter().map(to_toml).collect::<Result<_, _>>()?),
+        Value::Dictionary(v) => to_toml_table(v)?,
+        Value::Range { .. } => toml::Value::String(value.to_string()),
+        Value::Raw(v) => toml::Value::String(v.clone()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Ion;
+
+    #[test]
+    fn to_toml_string() {
+        let ion: Ion = r#"
+            [CONTRACT]
+            id = "HOTEL001"
+            nights = 7
+            refundable = true
+            loc = ["M", "B"]
+            dist = { beach_km = 4.1 }
+            | dropped | row |
+        "#
+        .parse()
+        .unwrap();
+
+        let toml = ion.to_toml_string().unwrap();
+        let parsed: toml::Value = toml.parse().unwrap();
+
+        let contract = &parsed["CONTRACT"];
+        assert_eq!(Some("HOTEL001"), contract["id"].as_str());
+        assert_eq!(Some(7), contract["nights"].as_integer());
+        assert_eq!(Some(true), contract["refundable"].as_bool());
+        assert_eq!(2, contract["loc"].as_array().unwrap().len());
+        assert_eq!(Some(4.1), contract["dist"]["beach_km"].as_float());
+        assert_eq!(5, contract.as_table().unwrap().len());
+    }
+}
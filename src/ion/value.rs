@@ -1,14 +1,62 @@
+use crate::parser::parse_scalar;
+#[cfg(feature = "datetime")]
+use crate::DateTime;
 use crate::{Dictionary, FromIon, IonError, Row};
-use std::str::FromStr;
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::str::FromStr;
 
+/// A value of an entry or cell. Variants are added by features and new syntax, so
+/// matches need a wildcard arm.
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Value {
     String(String),
     Integer(i64),
+    /// An integer literal which doesn't fit into `i64`.
+    #[cfg(feature = "i128")]
+    BigInt(i128),
     Float(f64),
     Boolean(bool),
+    /// An RFC 3339 timestamp with an offset, such as `2024-06-01T12:30:00Z`.
+    #[cfg(feature = "datetime")]
+    DateTime(DateTime),
     Array(Row),
     Dictionary(Dictionary),
+    /// An integer range, `start..end` or `start..=end` when `inclusive`, see `Parser::with_ranges`.
+    Range {
+        start: i64,
+        end: i64,
+        inclusive: bool,
+    },
+    /// The verbatim text of a value which couldn't be read, see `Parser::with_raw_fallback`.
+    Raw(String),
+}
+
+/// A numeric value, see `Value::as_number`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Returns the number as `f64`, large integers lose precision.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::Integer(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Number::Integer(_))
+    }
 }
 
 impl Value {
@@ -28,10 +76,16 @@ impl Value {
         match self {
             Value::String(..) => "string",
             Value::Integer(..) => "integer",
+            #[cfg(feature = "i128")]
+            Value::BigInt(..) => "integer",
             Value::Float(..) => "float",
             Value::Boolean(..) => "boolean",
+            #[cfg(feature = "datetime")]
+            Value::DateTime(..) => "datetime",
             Value::Array(..) => "array",
             Value::Dictionary(..) => "dictionary",
+            Value::Range { .. } => "range",
+            Value::Raw(..) => "raw",
         }
     }
 
@@ -42,6 +96,14 @@ impl Value {
         }
     }
 
+    /// Returns the text of a value kept uninterpreted by `Parser::with_raw_fallback`.
+    pub fn as_raw(&self) -> Option<&str> {
+        match self {
+            Value::Raw(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+
     pub fn is_string(&self) -> bool {
         matches!(self, Value::String(_))
     }
@@ -60,6 +122,17 @@ impl Value {
         }
     }
 
+    /// Returns the integer widened to `i128`, including `Value::BigInt` when the `i128`
+    /// feature is enabled.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Value::Integer(v) => Some(*v as i128),
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) => Some(*v),
+            _ => None,
+        }
+    }
+
     pub fn as_float(&self) -> Option<f64> {
         match self {
             Value::Float(v) => Some(*v),
@@ -67,6 +140,59 @@ impl Value {
         }
     }
 
+    /// Returns an `Integer` or `Float` value as a `Number`, any other value
+    /// including `BigInt` as `None`.
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Integer(v) => Some(Number::Integer(*v)),
+            Value::Float(v) => Some(Number::Float(*v)),
+            _ => None,
+        }
+    }
+
+    /// Adds two numeric values.
+    ///
+    /// Two integers add up to an integer, failing with `IonError::Overflow` when
+    /// the sum doesn't fit into `i64`. When either operand is a float, both are
+    /// added as floats, which never fails. Any other operand, `BigInt` included,
+    /// fails with `IonError::UnexpectedType`.
+    pub fn checked_add(&self, other: &Value) -> Result<Value, IonError> {
+        let number = |value: &Value| {
+            value.as_number().ok_or_else(|| IonError::UnexpectedType {
+                expected: "number",
+                found: value.type_str(),
+            })
+        };
+
+        match (number(self)?, number(other)?) {
+            (Number::Integer(a), Number::Integer(b)) => a
+                .checked_add(b)
+                .map(Value::Integer)
+                .ok_or(IonError::Overflow),
+            (a, b) => Ok(Value::Float(a.as_f64() + b.as_f64())),
+        }
+    }
+
+    #[cfg(feature = "datetime")]
+    pub fn as_datetime(&self) -> Option<&DateTime> {
+        match self {
+            Value::DateTime(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the `start`, `end` and `inclusive` fields of a `Range`.
+    pub fn as_range(&self) -> Option<(i64, i64, bool)> {
+        match *self {
+            Value::Range {
+                start,
+                end,
+                inclusive,
+            } => Some((start, end, inclusive)),
+            _ => None,
+        }
+    }
+
     pub fn as_boolean(&self) -> Option<bool> {
         match self {
             Value::Boolean(v) => Some(*v),
@@ -74,6 +200,61 @@ impl Value {
         }
     }
 
+    /// Loosely interprets the value as an on/off flag, unlike the strict `as_boolean`:
+    ///
+    /// - `Boolean` as is,
+    /// - `Integer` and `Float` when non-zero, `NaN` is falsy,
+    /// - `String` when it's one of `true`, `yes`, `on` or `1`, ignoring ASCII case and
+    ///   surrounding whitespace; any other string, the empty one included, is falsy,
+    /// - `DateTime` always,
+    /// - `Array`, `Dictionary` and `Range` when non-empty,
+    /// - `Raw` never, as it wasn't interpreted.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Boolean(v) => *v,
+            Value::Integer(v) => *v != 0,
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) => *v != 0,
+            Value::Float(v) => *v != 0.0 && !v.is_nan(),
+            Value::String(v) => {
+                let v = v.trim();
+                ["true", "yes", "on", "1"]
+                    .iter()
+                    .any(|truthy| v.eq_ignore_ascii_case(truthy))
+            }
+            #[cfg(feature = "datetime")]
+            Value::DateTime(_) => true,
+            Value::Array(v) => !v.is_empty(),
+            Value::Dictionary(v) => !v.is_empty(),
+            Value::Range {
+                start,
+                end,
+                inclusive,
+            } => start < end || (*inclusive && start == end),
+            Value::Raw(_) => false,
+        }
+    }
+
+    /// Compares like `==`, except that floats are equal when they differ by at most `epsilon`.
+    ///
+    /// Arrays and dictionaries are compared element-wise, recursively.
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        match (self, other) {
+            // `f64::abs` isn't available without `std`.
+            (Value::Float(a), Value::Float(b)) => a == b || (a - b <= epsilon && b - a <= epsilon),
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Value::Dictionary(a), Value::Dictionary(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, a), (kb, b))| ka == kb && a.approx_eq(b, epsilon))
+            }
+            _ => self == other,
+        }
+    }
+
     pub fn as_array(&self) -> Option<&Vec<Value>> {
         match self {
             Value::Array(v) => Some(v),
@@ -81,6 +262,46 @@ impl Value {
         }
     }
 
+    /// Converts every element of an array with `T::try_from`, returning `None` when
+    /// `self` is not an array or any element fails to convert.
+    pub fn as_array_of<'a, T: TryFrom<&'a Value>>(&'a self) -> Option<Vec<T>> {
+        self.as_array()?
+            .iter()
+            .map(|value| T::try_from(value).ok())
+            .collect()
+    }
+
+    /// Like `as_array_of`, but skips the elements failing to convert instead.
+    pub fn as_array_of_lossy<'a, T: TryFrom<&'a Value>>(&'a self) -> Option<Vec<T>> {
+        Some(
+            self.as_array()?
+                .iter()
+                .filter_map(|value| T::try_from(value).ok())
+                .collect(),
+        )
+    }
+
+    /// Returns the elements of an array with nested arrays flattened up to `depth` levels,
+    /// or `None` for any other value.
+    ///
+    /// With a `depth` of `0` the elements are returned as they are, `1` flattens arrays
+    /// nested directly in `self`, and `usize::MAX` flattens all levels.
+    pub fn flatten(&self, depth: usize) -> Option<Vec<&Value>> {
+        fn flatten_into<'a>(values: &'a [Value], depth: usize, out: &mut Vec<&'a Value>) {
+            for value in values {
+                match value {
+                    Value::Array(v) if depth > 0 => flatten_into(v, depth - 1, out),
+                    _ => out.push(value),
+                }
+            }
+        }
+
+        let values = self.as_array()?;
+        let mut out = Vec::with_capacity(values.len());
+        flatten_into(values, depth, &mut out);
+        Some(out)
+    }
+
     pub fn as_dictionary(&self) -> Option<&Dictionary> {
         match self {
             Value::Dictionary(v) => Some(v),
@@ -88,6 +309,15 @@ impl Value {
         }
     }
 
+    /// Returns the elements of an array of dictionaries, such as
+    /// `items = [ { id = 1 }, { id = 2 } ]`, to be read like table rows.
+    ///
+    /// Returns `None` for any other value, or when an element isn't a dictionary.
+    /// An empty array yields an empty table.
+    pub fn as_table(&self) -> Option<Vec<&Dictionary>> {
+        self.as_array()?.iter().map(Value::as_dictionary).collect()
+    }
+
     pub fn get(&self, name: &str) -> Option<&Value> {
         match self {
             Value::Dictionary(v) => v.get(name),
@@ -95,6 +325,177 @@ impl Value {
         }
     }
 
+    /// Returns `true` if `self` is a dictionary holding `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        match self {
+            Value::Dictionary(v) => v.contains_key(key),
+            _ => false,
+        }
+    }
+
+    /// Returns the keys of a dictionary in order, or `None` for any other value.
+    pub fn keys(&self) -> Option<impl Iterator<Item = &String>> {
+        match self {
+            Value::Dictionary(v) => Some(v.keys()),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a dictionary as `(key, value)` pairs sorted by key,
+    /// or `None` for any other value.
+    pub fn as_object_entries(&self) -> Option<Vec<(&String, &Value)>> {
+        self.as_dictionary().map(|v| v.iter().collect())
+    }
+
+    /// Appends `value` to an array.
+    ///
+    /// Fails with `IonError::UnexpectedType`, leaving `self` untouched, if it is not a `Value::Array`.
+    pub fn push(&mut self, value: impl Into<Value>) -> Result<(), IonError> {
+        self.array_mut()?.push(value.into());
+        Ok(())
+    }
+
+    /// Moves all of `other` to the end of an array.
+    ///
+    /// Fails with `IonError::UnexpectedType`, leaving `self` untouched, if it is not a `Value::Array`.
+    pub fn append(&mut self, mut other: Vec<Value>) -> Result<(), IonError> {
+        self.array_mut()?.append(&mut other);
+        Ok(())
+    }
+
+    /// Sorts an array in place by `total_cmp`, other values are left untouched.
+    pub fn sort_array(&mut self) {
+        self.sort_array_by(Value::total_cmp);
+    }
+
+    /// Sorts an array in place by `compare`, other values are left untouched.
+    ///
+    /// The sort is stable, so elements comparing equal keep their order.
+    pub fn sort_array_by(&mut self, compare: impl FnMut(&Value, &Value) -> Ordering) {
+        if let Value::Array(v) = self {
+            v.sort_by(compare);
+        }
+    }
+
+    /// Orders any two values, as `Value` can't implement `Ord` because of floats.
+    ///
+    /// Values of different types are ordered booleans, numbers, strings, timestamps,
+    /// ranges, arrays, dictionaries, raw values. Numbers are compared by value, an
+    /// integer coming first when equal to a float, and floats like `f64::total_cmp`.
+    /// Integers are compared with floats as `f64`, so very large ones lose precision.
+    /// Timestamps are compared as instants, then by offset. Arrays and dictionaries
+    /// are compared element-wise.
+    ///
+    /// The order agrees with `==`, except that `-0.0` is less than `0.0` and `NaN`
+    /// equals itself.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        fn rank(value: &Value) -> u8 {
+            match value {
+                Value::Boolean(_) => 0,
+                Value::Integer(_) | Value::Float(_) => 1,
+                #[cfg(feature = "i128")]
+                Value::BigInt(_) => 1,
+                Value::String(_) => 2,
+                #[cfg(feature = "datetime")]
+                Value::DateTime(_) => 3,
+                Value::Range { .. } => 4,
+                Value::Array(_) => 5,
+                Value::Dictionary(_) => 6,
+                Value::Raw(_) => 7,
+            }
+        }
+
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::Float(a), b) if rank(b) == 1 => {
+                let b = b.as_i128().unwrap_or_default() as f64;
+                a.total_cmp(&b).then(Ordering::Greater)
+            }
+            (a, Value::Float(b)) if rank(a) == 1 => {
+                let a = a.as_i128().unwrap_or_default() as f64;
+                a.total_cmp(b).then(Ordering::Less)
+            }
+            (a, b) if rank(a) == 1 && rank(b) == 1 => a.as_i128().cmp(&b.as_i128()),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            #[cfg(feature = "datetime")]
+            (Value::DateTime(a), Value::DateTime(b)) => a
+                .unix_time()
+                .cmp(&b.unix_time())
+                .then(a.offset_minutes.cmp(&b.offset_minutes)),
+            (
+                Value::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                Value::Range {
+                    start: other_start,
+                    end: other_end,
+                    inclusive: other_inclusive,
+                },
+            ) => (start, end, inclusive).cmp(&(other_start, other_end, other_inclusive)),
+            (Value::Array(a), Value::Array(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Value::Dictionary(a), Value::Dictionary(b)) => a
+                .iter()
+                .zip(b)
+                .map(|((ka, a), (kb, b))| ka.cmp(kb).then_with(|| a.total_cmp(b)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Value::Raw(a), Value::Raw(b)) => a.cmp(b),
+            (a, b) => rank(a).cmp(&rank(b)),
+        }
+    }
+
+    fn array_mut(&mut self) -> Result<&mut Vec<Value>, IonError> {
+        match self {
+            Value::Array(v) => Ok(v),
+            _ => Err(IonError::UnexpectedType {
+                expected: "array",
+                found: self.type_str(),
+            }),
+        }
+    }
+
+    /// Returns a mutable reference to the value stored under `key` in a dictionary,
+    /// inserting the result of `f` first if the key is absent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not a `Value::Dictionary`.
+    pub fn get_or_insert_with(&mut self, key: &str, f: impl FnOnce() -> Value) -> &mut Value {
+        match self {
+            Value::Dictionary(v) => v.entry(key.to_owned()).or_insert_with(f),
+            _ => panic!(
+                "Value::get_or_insert_with called on {}, expected dictionary",
+                self.type_str()
+            ),
+        }
+    }
+
+    /// Builds a value from text, inferring its type like the parser does for entry values:
+    /// `42` and `-42` become `Value::Integer`, `4.2` a `Value::Float` and `true` a `Value::Boolean`.
+    ///
+    /// Surrounding whitespace is ignored. Anything else, quoted text included,
+    /// falls back to a `Value::String` holding `s` verbatim.
+    pub fn from_str_inferred(s: &str) -> Value {
+        parse_scalar(s.trim()).unwrap_or_else(|| Value::String(s.to_owned()))
+    }
+
+    /// Calls `f` on every leaf of the value, recursing into arrays and dictionaries.
+    pub(crate) fn map_leaves(&mut self, f: &mut impl FnMut(&mut Value)) {
+        match self {
+            Value::Array(v) => v.iter_mut().for_each(|v| v.map_leaves(f)),
+            Value::Dictionary(v) => v.values_mut().for_each(|v| v.map_leaves(f)),
+            _ => f(self),
+        }
+    }
+
     pub fn from_ion<F>(&self) -> Result<F, F::Err>
     where
         F: FromIon<Value>,
@@ -113,6 +514,7 @@ impl Value {
     }
 }
 
+/// Always produces a `Value::String`, see `Value::from_str_inferred` for type inference.
 impl FromStr for Value {
     type Err = IonError;
 
@@ -121,19 +523,443 @@ impl FromStr for Value {
     }
 }
 
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.to_owned())
+    }
+}
+
+macro_rules! from_int_impl {
+    ($($t:ty)*) => {$(
+        impl From<$t> for Value {
+            fn from(v: $t) -> Self {
+                Value::Integer(v.into())
+            }
+        }
+    )*}
+}
+
+from_int_impl! { i8 i16 i32 i64 u8 u16 u32 }
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::Float(v)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Boolean(v)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(v: Vec<Value>) -> Self {
+        Value::Array(v)
+    }
+}
+
+impl From<Dictionary> for Value {
+    fn from(v: Dictionary) -> Self {
+        Value::Dictionary(v)
+    }
+}
+
+/// Strict conversions out of a `Value`, failing with `IonError::UnexpectedType`
+/// for any other variant. Unlike `FromIon`, strings are never parsed.
+macro_rules! try_from_value_impl {
+    ($($t:ty => $as:ident, $expected:literal;)*) => {$(
+        impl<'a> TryFrom<&'a Value> for $t {
+            type Error = IonError;
+
+            fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+                value.$as().map(Into::into).ok_or(IonError::UnexpectedType {
+                    expected: $expected,
+                    found: value.type_str(),
+                })
+            }
+        }
+    )*}
+}
+
+try_from_value_impl! {
+    i64 => as_integer, "integer";
+    f64 => as_float, "float";
+    bool => as_boolean, "boolean";
+    &'a str => as_str, "string";
+    String => as_str, "string";
+}
+
+/// Consistent with `PartialEq`: floats hash by their bits, with `-0.0` hashed as `0.0`.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Value::String(v) => v.hash(state),
+            Value::Integer(v) => v.hash(state),
+            #[cfg(feature = "i128")]
+            Value::BigInt(v) => v.hash(state),
+            Value::Float(v) => {
+                let v = if *v == 0.0 { 0.0 } else { *v };
+                v.to_bits().hash(state)
+            }
+            Value::Boolean(v) => v.hash(state),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(v) => v.hash(state),
+            Value::Array(v) => v.hash(state),
+            Value::Dictionary(v) => v.hash(state),
+            Value::Range {
+                start,
+                end,
+                inclusive,
+            } => (start, end, inclusive).hash(state),
+            Value::Raw(v) => v.hash(state),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::Value;
+    use crate::{Dictionary, Ion, IonError, Number, Value};
 
     #[test]
     fn integer() {
         let v: Value = "1".parse().unwrap();
-        assert_eq!(1, v.parse().unwrap());
+        assert_eq!(1, v.parse::<i32>().unwrap());
     }
 
     #[test]
     fn float() {
         let v: Value = "4.0".parse().unwrap();
-        assert_eq!(4.0f64, v.parse().unwrap());
+        assert_eq!(4.0f64, v.parse::<f64>().unwrap());
+    }
+
+    #[test]
+    fn as_array_of() {
+        let v = crate::parse_value("[1, 2, 3]").unwrap();
+        assert_eq!(Some(vec![1i64, 2, 3]), v.as_array_of());
+        assert_eq!(None, v.as_array_of::<&str>());
+
+        let v = crate::parse_value(r#"[1, "two", 3]"#).unwrap();
+        assert_eq!(None, v.as_array_of::<i64>());
+        assert_eq!(Some(vec![1i64, 3]), v.as_array_of_lossy());
+        assert_eq!(Some(vec!["two"]), v.as_array_of_lossy());
+
+        assert_eq!(None, Value::Integer(1).as_array_of::<i64>());
+        assert!(matches!(
+            i64::try_from(&Value::Float(1.0)),
+            Err(IonError::UnexpectedType {
+                expected: "integer",
+                found: "float"
+            })
+        ));
+    }
+
+    #[test]
+    fn contains_key_and_keys() {
+        let ion: Ion = r#"
+            R75042 = {
+                view = "SV"
+                loc  = ["M", "B"]
+                dist = { beach_km = 4.1 }
+            }
+        "#
+        .parse()
+        .unwrap();
+        let v = ion.get("root").unwrap().get("R75042").unwrap();
+
+        assert!(v.contains_key("dist"));
+        assert!(!v.contains_key("beach_km"));
+        assert_eq!(
+            vec!["dist", "loc", "view"],
+            v.keys().unwrap().collect::<Vec<_>>()
+        );
+
+        let view = v.get("view").unwrap();
+        assert!(!view.contains_key("dist"));
+        assert!(view.keys().is_none());
+    }
+
+    #[test]
+    fn as_object_entries() {
+        let mut dict = Dictionary::new();
+        dict.insert("view".to_owned(), Value::new_string("SV"));
+        dict.insert("dist".to_owned(), Value::Float(4.1));
+        let v = Value::Dictionary(dict);
+
+        let entries = v.as_object_entries().unwrap();
+
+        assert_eq!(2, entries.len());
+        assert_eq!(
+            ("dist", &Value::Float(4.1)),
+            (entries[0].0.as_str(), entries[0].1)
+        );
+        assert_eq!(
+            ("view", &Value::new_string("SV")),
+            (entries[1].0.as_str(), entries[1].1)
+        );
+        assert_eq!(None, Value::Integer(1).as_object_entries());
+    }
+
+    #[test]
+    fn push_and_append() {
+        let mut v = Value::Array(vec![]);
+
+        v.push(1).unwrap();
+        v.append(vec![Value::Integer(2), Value::Integer(3)])
+            .unwrap();
+
+        assert_eq!("[ 1, 2, 3 ]", v.to_string());
+    }
+
+    #[test]
+    fn push_on_non_array() {
+        let mut v = Value::Integer(1);
+
+        let err = v.push("foo").unwrap_err();
+
+        assert!(matches!(
+            err,
+            IonError::UnexpectedType {
+                expected: "array",
+                found: "integer"
+            }
+        ));
+        assert_eq!(Value::Integer(1), v);
+    }
+
+    #[test]
+    fn is_truthy() {
+        let mut dict = Dictionary::new();
+        dict.insert("key".to_owned(), Value::Integer(0));
+
+        let truthy = [
+            Value::Boolean(true),
+            Value::Integer(1),
+            Value::Integer(-1),
+            Value::Float(0.5),
+            Value::new_string("true"),
+            Value::new_string("TRUE"),
+            Value::new_string("yes"),
+            Value::new_string("On"),
+            Value::new_string(" 1 "),
+            Value::Array(vec![Value::Boolean(false)]),
+            Value::Dictionary(dict),
+        ];
+        for v in &truthy {
+            assert!(v.is_truthy(), "{v:?} should be truthy");
+        }
+
+        let falsy = [
+            Value::Boolean(false),
+            Value::Integer(0),
+            Value::Float(0.0),
+            Value::Float(-0.0),
+            Value::Float(f64::NAN),
+            Value::new_string(""),
+            Value::new_string("false"),
+            Value::new_string("no"),
+            Value::new_string("off"),
+            Value::new_string("0"),
+            Value::new_string("enabled"),
+            Value::Array(vec![]),
+            Value::Dictionary(Dictionary::new()),
+        ];
+        for v in &falsy {
+            assert!(!v.is_truthy(), "{v:?} should be falsy");
+        }
+    }
+
+    #[test]
+    fn flatten() {
+        let v = Value::Array(vec![
+            Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            Value::Array(vec![Value::Integer(3)]),
+        ]);
+
+        let flat = v.flatten(1).unwrap();
+        assert_eq!(
+            vec![&Value::Integer(1), &Value::Integer(2), &Value::Integer(3)],
+            flat
+        );
+        assert_eq!(2, v.flatten(0).unwrap().len());
+
+        let nested = Value::Array(vec![Value::Integer(1), v]);
+        assert_eq!(3, nested.flatten(1).unwrap().len());
+        assert_eq!(4, nested.flatten(usize::MAX).unwrap().len());
+
+        assert_eq!(None, Value::Integer(1).flatten(1));
+    }
+
+    #[test]
+    fn approx_eq() {
+        assert_ne!(Value::Float(0.1 + 0.2), Value::Float(0.3));
+        assert!(Value::Float(0.1 + 0.2).approx_eq(&Value::Float(0.3), 1e-9));
+        assert!(!Value::Float(0.31).approx_eq(&Value::Float(0.3), 1e-9));
+
+        let mut a = Dictionary::new();
+        a.insert("x".to_owned(), Value::Array(vec![Value::Float(0.1 + 0.2)]));
+        let mut b = Dictionary::new();
+        b.insert("x".to_owned(), Value::Array(vec![Value::Float(0.3)]));
+        assert!(Value::Dictionary(a).approx_eq(&Value::Dictionary(b.clone()), 1e-9));
+
+        let mut c = Dictionary::new();
+        c.insert("y".to_owned(), Value::Array(vec![Value::Float(0.3)]));
+        assert!(!Value::Dictionary(c).approx_eq(&Value::Dictionary(b), 1e-9));
+
+        assert!(!Value::Integer(1).approx_eq(&Value::Float(1.0), 1e-9));
+        assert!(Value::new_string("a").approx_eq(&Value::new_string("a"), 1e-9));
+    }
+
+    #[test]
+    fn sort_array() {
+        let mut value = Value::Array(vec![
+            Value::new_string("b"),
+            Value::Float(1.5),
+            Value::Boolean(true),
+            Value::Integer(2),
+            Value::new_string("a"),
+            Value::Integer(1),
+            Value::Float(1.0),
+        ]);
+        value.sort_array();
+        assert_eq!(
+            &vec![
+                Value::Boolean(true),
+                Value::Integer(1),
+                Value::Float(1.0),
+                Value::Float(1.5),
+                Value::Integer(2),
+                Value::new_string("a"),
+                Value::new_string("b"),
+            ],
+            value.as_array().unwrap()
+        );
+
+        let mut value = Value::Array(vec![
+            Value::new_string("pear"),
+            Value::new_string("apple"),
+            Value::new_string("fig"),
+        ]);
+        value.sort_array_by(|a, b| a.as_str().map(str::len).cmp(&b.as_str().map(str::len)));
+        let sorted: Vec<_> = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        assert_eq!(vec!["fig", "pear", "apple"], sorted);
+
+        let mut value = Value::Integer(1);
+        value.sort_array();
+        assert_eq!(Value::Integer(1), value);
+    }
+
+    #[test]
+    fn as_number() {
+        let n = Value::Integer(3).as_number().unwrap();
+        assert_eq!(Number::Integer(3), n);
+        assert!(n.is_integer());
+        assert_eq!(3.0, n.as_f64());
+
+        let n = Value::Float(3.5).as_number().unwrap();
+        assert_eq!(Number::Float(3.5), n);
+        assert!(!n.is_integer());
+        assert_eq!(3.5, n.as_f64());
+
+        assert_eq!(None, Value::new_string("3").as_number());
+    }
+
+    #[test]
+    fn as_table() {
+        let ion: Ion = "[FOO]\nitems = [ { id = 1 }, { id = 2 } ]\nmixed = [ { id = 1 }, 2 ]"
+            .parse()
+            .unwrap();
+        let section = ion.get("FOO").unwrap();
+
+        let table = section.get("items").and_then(Value::as_table).unwrap();
+        let ids: Vec<_> = table.iter().map(|d| d["id"].as_integer()).collect();
+        assert_eq!(vec![Some(1), Some(2)], ids);
+
+        assert_eq!(None, section.get("mixed").and_then(Value::as_table));
+        assert_eq!(None, Value::Integer(1).as_table());
+    }
+
+    #[test]
+    fn checked_add() {
+        let sum = Value::Integer(1).checked_add(&Value::Integer(2)).unwrap();
+        assert_eq!(Value::Integer(3), sum);
+
+        let sum = Value::Integer(1).checked_add(&Value::Float(0.5)).unwrap();
+        assert_eq!(Value::Float(1.5), sum);
+
+        let err = Value::Integer(i64::MAX)
+            .checked_add(&Value::Integer(1))
+            .unwrap_err();
+        assert!(matches!(err, IonError::Overflow));
+
+        let err = Value::Float(1.0)
+            .checked_add(&Value::new_string("1"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IonError::UnexpectedType {
+                expected: "number",
+                found: "string"
+            }
+        ));
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut v = Value::Dictionary(Dictionary::new());
+
+        v.get_or_insert_with("nested", || Value::Dictionary(Dictionary::new()))
+            .get_or_insert_with("key", || Value::Integer(1));
+        v.get_or_insert_with("nested", || unreachable!())
+            .get_or_insert_with("key", || unreachable!());
+
+        let nested = v.get("nested").unwrap();
+        assert_eq!(Some(1), nested.get("key").and_then(Value::as_integer));
+        assert_eq!(1, nested.as_dictionary().unwrap().len());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected dictionary")]
+    fn get_or_insert_with_on_non_dictionary() {
+        let mut v = Value::Integer(1);
+        v.get_or_insert_with("key", || Value::Integer(2));
+    }
+
+    #[test]
+    fn from_str_inferred() {
+        assert_eq!(Value::Integer(42), Value::from_str_inferred("42"));
+        assert_eq!(Value::Integer(42), Value::from_str_inferred(" 42 "));
+        assert_eq!(Value::Float(4.5), Value::from_str_inferred("4.5"));
+        assert_eq!(Value::Integer(-42), Value::from_str_inferred("-42"));
+        assert_eq!(Value::new_string("-"), Value::from_str_inferred("-"));
+        assert_eq!(Value::Boolean(true), Value::from_str_inferred("true"));
+        assert_eq!(Value::Boolean(false), Value::from_str_inferred("false"));
+        assert_eq!(Value::new_string("foo"), Value::from_str_inferred("foo"));
+        assert_eq!(
+            Value::new_string("42abc"),
+            Value::from_str_inferred("42abc")
+        );
+        assert_eq!(
+            Value::new_string("truely"),
+            Value::from_str_inferred("truely")
+        );
+        assert_eq!(
+            Value::new_string("\"42\""),
+            Value::from_str_inferred("\"42\"")
+        );
+        assert_eq!(Value::new_string(""), Value::from_str_inferred(""));
     }
 }
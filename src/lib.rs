@@ -1,10 +1,16 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 #[macro_use]
 mod ion;
 mod parser;
 
 pub use self::ion::*;
 pub use self::parser::*;
-use std::collections::BTreeMap;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
 
 pub type Dictionary = BTreeMap<String, Value>;
 pub type Row = Vec<Value>;
@@ -1,50 +1,252 @@
-use crate::{Section, Value};
-use std::collections::BTreeMap;
-use std::iter::Peekable;
-use std::{error, fmt, str};
+#[cfg(feature = "datetime")]
+use crate::ion::datetime::{self, Lexeme};
+use crate::ion::line_column;
+use crate::{Dictionary, IonError, Row, Section, Value};
+use alloc::borrow::ToOwned;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::iter::Peekable;
+use core::{fmt, mem, str};
 
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Element {
     Section(String),
     Row(Vec<Value>),
     Entry(String, Value),
     Comment(String),
+    /// A comment following an entry or a row on the same line, right after
+    /// that element, see `Parser::with_trailing_comments`.
+    TrailingComment(String),
+    /// An explicit end of the named section, see `Parser::with_section_end_markers`.
+    SectionEnd(String),
 }
 
-pub struct Parser<'a> {
+/// Counters describing how much work a single [`Parser::read_with_stats`] call did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Number of section headers read, the implicit root section is not counted.
+    pub sections: usize,
+    pub entries: usize,
+    pub rows: usize,
+    pub comments: usize,
+    /// Number of input bytes consumed before the parser stopped.
+    pub bytes: usize,
+}
+
+/// An array, dictionary or anchor being read, see `Parser::value`.
+enum Nested {
+    Array(Row),
+    /// The entries read so far, and the key of the entry being read.
+    Dictionary(Dictionary, String),
+    /// The name of an anchor whose value is being read.
+    Anchor(String),
+}
+
+enum ValueStart {
+    Value(Value),
+    Nested(Nested),
+}
+
+/// A parser of Ion documents. `F` is the callback given to `with_on_error`.
+pub struct Parser<'a, F = fn(&ParserError) -> ErrorAction> {
     input: &'a str,
     cur: Peekable<str::CharIndices<'a>>,
-    pub(crate) errors: Vec<ParserError>,
+    /// Errors accumulated while parsing, those skipped by `with_on_error` included.
+    /// `read` returns `None` when there are any others, they stay available here afterwards.
+    pub errors: Vec<ParserError>,
+    /// Number of leading `errors` skipped by `with_on_error`.
+    skipped_errors: usize,
+    warnings: Vec<Warning>,
+    indent_lint: bool,
     accepted_sections: Option<Vec<&'a str>>,
+    root_in_filter: bool,
     section_capacity: usize,
     row_capacity: usize,
     array_capacity: usize,
+    number_fallback_string: bool,
+    raw_fallback: bool,
+    bareword_values: bool,
+    repeated_keys_as_array: bool,
+    keyval_separator: char,
+    comment_prefixes: Vec<&'a str>,
+    strict_keys: bool,
+    percent: bool,
+    ranges: bool,
+    heredocs: bool,
+    array_delimiters: (char, char),
+    dictionary_delimiters: (char, char),
+    trim_quoted: bool,
+    raw_backslash: bool,
+    anchors: Option<BTreeMap<String, Value>>,
+    /// Number of values copied by anchor references so far.
+    anchor_expansion: usize,
+    max_depth: usize,
+    max_input_size: Option<usize>,
+    input_size_checked: bool,
+    spans: bool,
+    section_attributes: Dictionary,
+    section_end_markers: bool,
+    inline_sections: bool,
+    preamble: bool,
+    trailing_comments: bool,
+    trailing_comment: Option<String>,
+    on_error: Option<F>,
+    require_sections: bool,
+    current_section: Option<String>,
+    section_start: usize,
+    section_end: Option<usize>,
+    /// End of the line holding the last section header, before which a `|` ends a
+    /// bareword or raw value, see `with_inline_sections`.
+    header_line_end: usize,
 }
 
-impl<'a> Iterator for Parser<'a> {
+impl<'a, F: FnMut(&ParserError) -> ErrorAction> Iterator for Parser<'a, F> {
     type Item = Element;
 
     fn next(&mut self) -> Option<Element> {
+        if !self.input_size_checked {
+            self.input_size_checked = true;
+            if self.check_input_size().is_err() {
+                self.add_error(ParserErrorKind::Syntax, "Input exceeds the maximum size");
+                self.cur = "".char_indices().peekable();
+                return None;
+            }
+        }
+
+        if let Some(comment) = self.trailing_comment.take() {
+            return Some(Element::TrailingComment(comment));
+        }
+
+        loop {
+            let errors = self.errors.len();
+            match self.element() {
+                None if self.errors.len() > errors && self.recover(errors) => continue,
+                element => return element,
+            }
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self::new_filtered_opt(s, None)
+    }
+
+    pub fn new_filtered(s: &'a str, accepted_sections: Vec<&'a str>) -> Self {
+        Self::new_filtered_opt(s, Some(accepted_sections))
+    }
+
+    fn new_filtered_opt(s: &'a str, accepted_sections: Option<Vec<&'a str>>) -> Self {
+        Self {
+            input: s,
+            cur: s.char_indices().peekable(),
+            errors: Vec::new(),
+            skipped_errors: 0,
+            warnings: Vec::new(),
+            indent_lint: false,
+            accepted_sections,
+            root_in_filter: false,
+            section_capacity: 16,
+            row_capacity: 8,
+            array_capacity: 2,
+            number_fallback_string: false,
+            raw_fallback: false,
+            bareword_values: false,
+            repeated_keys_as_array: false,
+            on_error: None,
+            keyval_separator: '=',
+            comment_prefixes: vec!["#"],
+            strict_keys: false,
+            percent: false,
+            ranges: false,
+            heredocs: false,
+            array_delimiters: ('[', ']'),
+            dictionary_delimiters: ('{', '}'),
+            trim_quoted: false,
+            raw_backslash: false,
+            anchors: None,
+            anchor_expansion: 0,
+            max_depth: 128,
+            max_input_size: None,
+            input_size_checked: false,
+            spans: false,
+            section_attributes: Dictionary::new(),
+            section_end_markers: false,
+            inline_sections: false,
+            preamble: false,
+            trailing_comments: false,
+            trailing_comment: None,
+            require_sections: false,
+            current_section: None,
+            section_start: 0,
+            section_end: None,
+            header_line_end: 0,
+        }
+    }
+}
+
+impl<'a, F: FnMut(&ParserError) -> ErrorAction> Parser<'a, F> {
+    fn element(&mut self) -> Option<Element> {
         let mut is_section_accepted = true;
 
         loop {
+            let line_start = self.byte_offset();
             self.whitespace();
+            self.lint_indent(line_start);
 
             if self.newline() {
                 continue;
             }
 
-            let c = match self.cur.peek() {
-                Some((_, c)) => *c,
+            let (pos, c) = match self.cur.peek() {
+                Some((pos, c)) => (*pos, *c),
                 None => return None,
             };
 
             if c == '[' {
                 let name = self.section_name();
 
+                if self.spans && self.section_end.is_none() {
+                    self.section_end = Some(pos);
+                }
+
+                if self.section_end_markers && (name.is_empty() || name.starts_with('/')) {
+                    return self.section_end_marker(&name).map(Element::SectionEnd);
+                }
+
+                if self.require_sections && name.is_empty() {
+                    self.add_error(ParserErrorKind::Syntax, "Content outside of any section");
+                    return None;
+                }
+
+                let name = match name.is_empty() {
+                    true => Section::DEFAULT_NAME.to_owned(),
+                    false => name,
+                };
+
+                self.current_section = Some(name.clone());
+
                 match self.is_section_accepted(&name) {
-                    Some(true) => return Some(Element::Section(name)),
-                    Some(false) => is_section_accepted = false,
+                    Some(true) => {
+                        self.section_start = pos;
+                        self.section_attributes = match self.cur.peek() {
+                            Some((_, '(')) => self.section_attributes()?,
+                            _ => Dictionary::new(),
+                        };
+                        if self.inline_sections {
+                            let rest = self.remaining();
+                            self.header_line_end =
+                                self.byte_offset() + rest.find('\n').unwrap_or(rest.len());
+                        }
+                        return Some(Element::Section(name));
+                    }
+                    Some(false) => {
+                        self.skip_trailing_comment();
+                        is_section_accepted = false;
+                    }
                     None => return None,
                 }
             }
@@ -54,22 +256,53 @@ impl<'a> Iterator for Parser<'a> {
                 continue;
             }
 
+            if self.control_char() {
+                return None;
+            }
+
+            if c != '|' {
+                if let Some(comment) = self.comment() {
+                    return Some(comment);
+                }
+            }
+
+            if self.require_sections && self.current_section.is_none() {
+                self.add_error(ParserErrorKind::Syntax, "Content outside of any section");
+                return None;
+            }
+
             return match c {
                 '|' => self.row(),
-                '#' => self.comment(),
                 _ => self.entry(),
             };
         }
     }
-}
 
-impl<'a> Parser<'a> {
-    pub fn new(s: &'a str) -> Self {
-        Self::new_filtered_opt(s, None)
-    }
+    /// Asks the `with_on_error` callback how to go on after the error at `errors`,
+    /// returning `false` to stop. When skipping, the errors are marked as skipped.
+    fn recover(&mut self, errors: usize) -> bool {
+        let action = match self.on_error.as_mut() {
+            Some(on_error) => on_error(&self.errors[errors]),
+            None => ErrorAction::Abort,
+        };
 
-    pub fn new_filtered(s: &'a str, accepted_sections: Vec<&'a str>) -> Self {
-        Self::new_filtered_opt(s, Some(accepted_sections))
+        match action {
+            ErrorAction::Abort => return false,
+            ErrorAction::SkipLine => {
+                self.slice_to_including('\n');
+            }
+            ErrorAction::SkipSection => loop {
+                self.slice_to_including('\n');
+                let rest = self.remaining().trim_start_matches(&[' ', '\t'][..]);
+                if rest.is_empty() || rest.starts_with('[') {
+                    break;
+                }
+            },
+        }
+
+        self.skipped_errors = self.errors.len();
+        self.trailing_comment = None;
+        true
     }
 
     pub fn with_section_capacity(mut self, section_capacity: usize) -> Self {
@@ -87,15 +320,343 @@ impl<'a> Parser<'a> {
         self
     }
 
-    fn new_filtered_opt(s: &'a str, accepted_sections: Option<Vec<&'a str>>) -> Self {
-        Self {
-            input: s,
-            cur: s.char_indices().peekable(),
-            errors: Vec::new(),
-            accepted_sections,
-            section_capacity: 16,
-            row_capacity: 8,
-            array_capacity: 2,
+    /// Keeps a numeric literal which doesn't fit its type (e.g. an integer overflowing `i64`)
+    /// as a `Value::String` instead of failing the whole parse.
+    ///
+    /// This trades strictness for resilience: a malformed number is silently accepted as text,
+    /// so callers expecting a number have to handle the string case themselves.
+    pub fn with_number_fallback_string(mut self, number_fallback_string: bool) -> Self {
+        self.number_fallback_string = number_fallback_string;
+        self
+    }
+
+    /// Keeps an entry value which can't be read, e.g. written in a syntax added by a newer
+    /// version, as `Value::Raw` holding the rest of its line up to a comment instead of failing.
+    ///
+    /// Raw values are written back unchanged by `Display`, so documents round-trip as long
+    /// as they're read back in this mode. A value spanning several lines, such as an array
+    /// with an unknown element, is cut at the end of its first line. With `with_inline_sections`,
+    /// a `|` on the line of a section header ends the value too, e.g. `[FOO] key = @x | a |`.
+    pub fn with_raw_fallback(mut self, raw_fallback: bool) -> Self {
+        self.raw_fallback = raw_fallback;
+        self
+    }
+
+    /// Reads an entry value which isn't a literal, e.g. `mode = read-only`, as a
+    /// `Value::String` of the rest of its line up to a comment, trimmed, instead of failing.
+    ///
+    /// Values are recognized as with `with_raw_fallback`, which this takes precedence over.
+    /// Like there, anything following a literal other than a comment makes the whole line
+    /// a bareword, e.g. `version = 1.2.3`, and a `|` only ends the bareword on the line of
+    /// a section header with `with_inline_sections`.
+    pub fn with_bareword_values(mut self, bareword_values: bool) -> Self {
+        self.bareword_values = bareword_values;
+        self
+    }
+
+    /// Keeps comments at the end of an entry or row line, as in `port = 80 # http`,
+    /// instead of reading them as a standalone comment or dropping them.
+    ///
+    /// They're returned as `Element::TrailingComment` and kept in the section's
+    /// `entry_comments` and `row_comments`, which `Display` writes back.
+    pub fn with_trailing_comments(mut self, trailing_comments: bool) -> Self {
+        self.trailing_comments = trailing_comments;
+        self
+    }
+
+    /// Lets `on_error` decide how to go on after each error, instead of stopping
+    /// at the first one, which is what `ErrorAction::Abort` does.
+    ///
+    /// Skipping resumes after the line the error occurred on, or before the next
+    /// section header. The content read before the error is kept, and a skipped
+    /// error stays in `errors` without making `read` fail, so `read` succeeds unless
+    /// aborted.
+    pub fn with_on_error<G: FnMut(&ParserError) -> ErrorAction>(
+        self,
+        on_error: G,
+    ) -> Parser<'a, G> {
+        Parser {
+            input: self.input,
+            cur: self.cur,
+            errors: self.errors,
+            skipped_errors: self.skipped_errors,
+            warnings: self.warnings,
+            indent_lint: self.indent_lint,
+            accepted_sections: self.accepted_sections,
+            root_in_filter: self.root_in_filter,
+            section_capacity: self.section_capacity,
+            row_capacity: self.row_capacity,
+            array_capacity: self.array_capacity,
+            number_fallback_string: self.number_fallback_string,
+            raw_fallback: self.raw_fallback,
+            bareword_values: self.bareword_values,
+            repeated_keys_as_array: self.repeated_keys_as_array,
+            keyval_separator: self.keyval_separator,
+            comment_prefixes: self.comment_prefixes,
+            strict_keys: self.strict_keys,
+            percent: self.percent,
+            ranges: self.ranges,
+            heredocs: self.heredocs,
+            array_delimiters: self.array_delimiters,
+            dictionary_delimiters: self.dictionary_delimiters,
+            trim_quoted: self.trim_quoted,
+            raw_backslash: self.raw_backslash,
+            anchors: self.anchors,
+            anchor_expansion: self.anchor_expansion,
+            max_depth: self.max_depth,
+            max_input_size: self.max_input_size,
+            input_size_checked: self.input_size_checked,
+            spans: self.spans,
+            section_attributes: self.section_attributes,
+            section_end_markers: self.section_end_markers,
+            inline_sections: self.inline_sections,
+            preamble: self.preamble,
+            trailing_comments: self.trailing_comments,
+            trailing_comment: self.trailing_comment,
+            on_error: Some(on_error),
+            require_sections: self.require_sections,
+            current_section: self.current_section,
+            section_start: self.section_start,
+            section_end: self.section_end,
+            header_line_end: self.header_line_end,
+        }
+    }
+
+    /// Collects the values of a key repeated within a section into a `Value::Array`,
+    /// instead of keeping only the last one.
+    ///
+    /// A key occurring once keeps its value as is. The second occurrence turns it into
+    /// an array of both values, further ones are appended.
+    pub fn with_repeated_keys_as_array(mut self, repeated_keys_as_array: bool) -> Self {
+        self.repeated_keys_as_array = repeated_keys_as_array;
+        self
+    }
+
+    /// Keeps the implicit `root` section, i.e. the content before the first
+    /// section header, when reading with a section filter.
+    ///
+    /// Has no effect on parsers created without a filter.
+    pub fn with_root_in_filter(mut self, root_in_filter: bool) -> Self {
+        self.root_in_filter = root_in_filter;
+        self
+    }
+
+    /// Sets the character separating a key from its value, `=` by default.
+    ///
+    /// The separator is only looked for right after a key, so it may still
+    /// appear freely inside quoted strings.
+    pub fn with_keyval_separator(mut self, keyval_separator: char) -> Self {
+        self.keyval_separator = keyval_separator;
+        self
+    }
+
+    /// Sets the prefixes starting a comment, `#` by default.
+    ///
+    /// Comments are recognized at the start of a line and after a table cell.
+    /// Empty prefixes are ignored.
+    pub fn with_comment_prefixes(mut self, comment_prefixes: &[&'a str]) -> Self {
+        self.comment_prefixes = comment_prefixes
+            .iter()
+            .copied()
+            .filter(|prefix| !prefix.is_empty())
+            .collect();
+        self
+    }
+
+    /// Rejects keys starting with a digit, such as `0key` or `42`.
+    ///
+    /// Such keys are usually a sign of a misparsed table, and tend to trip up
+    /// `FromIon` implementations and serde mappings further down the line.
+    pub fn with_strict_keys(mut self, strict_keys: bool) -> Self {
+        self.strict_keys = strict_keys;
+        self
+    }
+
+    /// Reads a number immediately followed by `%` as a `Value::Float` holding
+    /// the number divided by 100, so `15%` becomes `0.15`.
+    ///
+    /// The literal form is not preserved: such a value is written back as `0.15`.
+    /// With the mode off, a `%` following a number is an error.
+    pub fn with_percent(mut self, percent: bool) -> Self {
+        self.percent = percent;
+        self
+    }
+
+    /// Reads integer ranges, `3..7` excluding and `3..=7` including the end, as `Value::Range`.
+    ///
+    /// Both bounds have to be integers, so `3.0..7.0` is an error rather than two floats.
+    pub fn with_ranges(mut self, ranges: bool) -> Self {
+        self.ranges = ranges;
+        self
+    }
+
+    /// Allows heredoc table cells spanning several lines:
+    ///
+    /// ```text
+    /// | 1 | <<END
+    /// first line
+    /// second line
+    /// END | three |
+    /// ```
+    ///
+    /// The cell holds the lines between `<<MARKER` and the closing marker verbatim,
+    /// without processing escapes. The closing marker has to start its line, the row
+    /// then goes on right after it. A heredoc counts as a single column, however many
+    /// lines it spans.
+    pub fn with_heredocs(mut self, heredocs: bool) -> Self {
+        self.heredocs = heredocs;
+        self
+    }
+
+    /// Allows YAML-like anchors for sharing values: `base = &name { ... }` defines
+    /// the anchor `name` for the value following it, and `copy = *name` expands to a copy of it.
+    ///
+    /// Anchors apply to entry values, arrays and dictionaries included, and are
+    /// visible from their definition to the end of the input, across sections.
+    /// Referencing an anchor before it's defined is an error, and so is expanding
+    /// more than 65,536 values in total, which guards against documents nesting
+    /// references to blow up exponentially.
+    pub fn with_anchors(mut self, anchors: bool) -> Self {
+        self.anchors = match anchors {
+            true => Some(BTreeMap::new()),
+            false => None,
+        };
+        self
+    }
+
+    /// Sets how deeply arrays, dictionaries and anchors may be nested in a value, 128 by default.
+    ///
+    /// Deeper input is rejected with an error instead of risking a stack overflow.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Rejects input longer than `max_input_size` bytes before reading any of it,
+    /// unlimited by default.
+    ///
+    /// `read` fails with an error, as does iterating, which yields no elements at all.
+    /// `Ion::from_parser` fails with `IonError::InputTooLarge`.
+    pub fn with_max_input_size(mut self, max_input_size: usize) -> Self {
+        self.max_input_size = Some(max_input_size);
+        self
+    }
+
+    /// Fails with `IonError::InputTooLarge` when the input exceeds `with_max_input_size`.
+    pub(crate) fn check_input_size(&self) -> Result<(), IonError> {
+        match self.max_input_size {
+            Some(max) if self.input.len() > max => Err(IonError::InputTooLarge {
+                size: self.input.len(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the characters enclosing an array value, `[` and `]` by default.
+    pub fn with_array_delimiters(mut self, open: char, close: char) -> Self {
+        self.array_delimiters = (open, close);
+        self
+    }
+
+    /// Sets the characters enclosing an inline dictionary value, `{` and `}` by default.
+    ///
+    /// Note that a line starting with `[` is always read as a section header, except
+    /// where a value is expected: a value may start on the line following its key, so
+    /// with `[` opening a dictionary, `key =` followed by a section header on the next
+    /// line reads the header as a value.
+    pub fn with_dictionary_delimiters(mut self, open: char, close: char) -> Self {
+        self.dictionary_delimiters = (open, close);
+        self
+    }
+
+    /// Trims leading and trailing whitespace inside quoted strings, so `" x "` reads as `x`.
+    ///
+    /// By default quoted strings are kept verbatim. Only literal whitespace is trimmed,
+    /// escaped characters such as `\n` are preserved.
+    pub fn with_trim_quoted(mut self, trim_quoted: bool) -> Self {
+        self.trim_quoted = trim_quoted;
+        self
+    }
+
+    /// Reads backslashes in quoted strings literally, so `"C:\temp\new"` reads as a path.
+    ///
+    /// By default `\n` and `\t` in quoted strings are turned into a newline and a tab,
+    /// which silently mangles Windows paths. In this mode there are no escapes at all,
+    /// so a quoted string ends at the next `"` and can't contain one.
+    pub fn with_raw_backslash(mut self, raw_backslash: bool) -> Self {
+        self.raw_backslash = raw_backslash;
+        self
+    }
+
+    /// Records a `Warning`, returned by `read_with_warnings`, for every line
+    /// indented with both tabs and spaces.
+    pub fn with_indent_lint(mut self, indent_lint: bool) -> Self {
+        self.indent_lint = indent_lint;
+        self
+    }
+
+    /// Reads content following a section header on the same line as an inline section,
+    /// e.g. `[FOO] mode = read-only | a | b |`, where a `|` ends a bareword or raw value
+    /// so that the rest of the line is a row of `FOO`.
+    ///
+    /// Attributes must directly follow the `]`, then come the entries, then the rows.
+    /// Without this, content after a header is still read into its section, but a
+    /// bareword or raw value runs to the end of the line as it does on any other line.
+    pub fn with_inline_sections(mut self, inline_sections: bool) -> Self {
+        self.inline_sections = inline_sections;
+        self
+    }
+
+    /// Recognizes `[/FOO]` as the end of section `FOO`, and a bare `[]` as the end
+    /// of whichever section is open. Closing a section that isn't open is an error.
+    ///
+    /// Content following an end marker goes to the implicit `root` section, as content
+    /// before the first header does in a document without sections. Content after several
+    /// end markers is gathered in the same `root` section. Content before the first header
+    /// is still dropped when the document has sections.
+    pub fn with_section_end_markers(mut self, section_end_markers: bool) -> Self {
+        self.section_end_markers = section_end_markers;
+        self
+    }
+
+    /// Rejects entries and rows outside of any section, i.e. before the first header
+    /// or after a section end marker, instead of reading them into the implicit `root` section.
+    /// An empty header `[]`, which names the `root` section, is rejected as well.
+    pub fn with_require_sections(mut self, require_sections: bool) -> Self {
+        self.require_sections = require_sections;
+        self
+    }
+
+    /// Keeps the comment block at the start of the document, returned by `preamble`
+    /// and `Ion::preamble` when parsing with `Ion::from_parser`.
+    pub fn with_preamble(mut self, preamble: bool) -> Self {
+        self.preamble = preamble;
+        self
+    }
+
+    /// Records in `Section::span` the byte range each section occupies in the input,
+    /// from its `[` up to just before the next section header.
+    pub fn with_spans(mut self, spans: bool) -> Self {
+        self.spans = spans;
+        self
+    }
+
+    /// Warns about mixed tabs and spaces in whitespace read from `start`, if it indents a line.
+    fn lint_indent(&mut self, start: usize) {
+        if !self.indent_lint || !(start == 0 || self.input[..start].ends_with('\n')) {
+            return;
+        }
+
+        let end = self.byte_offset();
+        let indent = &self.input[start..end];
+
+        if indent.contains(' ') && indent.contains('\t') {
+            self.warnings.push(Warning {
+                lo: start,
+                hi: end,
+                desc: "Indentation mixes tabs and spaces".to_owned(),
+            });
         }
     }
 
@@ -128,9 +689,31 @@ impl<'a> Parser<'a> {
         self.cur.by_ref().find(|&(_, c)| c != '\n');
     }
 
+    /// Skips a comment following the header of a filtered out section on the same
+    /// line, so that e.g. a `[` in it isn't taken for a header while skipping the section.
+    fn skip_trailing_comment(&mut self) {
+        self.whitespace();
+        let input = self.input;
+        let rest = &input[self.byte_offset()..];
+        if self
+            .comment_prefixes
+            .iter()
+            .any(|prefix| rest.starts_with(*prefix))
+        {
+            self.slice_to_including('\n');
+        }
+    }
+
     fn comment(&mut self) -> Option<Element> {
-        if !self.eat('#') {
-            return None;
+        let input = self.input;
+        let rest = &input[self.byte_offset()..];
+        let prefix = self
+            .comment_prefixes
+            .iter()
+            .find(|prefix| rest.starts_with(**prefix))?;
+
+        for _ in prefix.chars() {
+            self.cur.next();
         }
 
         Some(Element::Comment(
@@ -138,6 +721,11 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    fn keep_trailing_comment(&mut self, comment: String) {
+        let comment = comment.trim_end_matches(&['\n', '\r'][..]);
+        self.trailing_comment = Some(comment.to_owned());
+    }
+
     fn eat(&mut self, ch: char) -> bool {
         match self.cur.peek() {
             Some((_, c)) if *c == ch => {
@@ -152,128 +740,394 @@ impl<'a> Parser<'a> {
         self.eat('[');
         self.whitespace();
 
-        self.cur
-            .by_ref()
-            .map(|(_, c)| c)
-            .take_while(|c| *c != ']')
-            .collect()
+        let start = self.byte_offset();
+        let end = match self.cur.by_ref().find(|&(_, c)| c == ']') {
+            Some((end, _)) => end,
+            None => self.input.len(),
+        };
+        let name = &self.input[start..end];
+        self.reject_control_chars(name, start);
+
+        name.to_owned()
     }
 
-    fn entry(&mut self) -> Option<Element> {
-        if let Some(key) = self.key_name() {
-            if !self.keyval_sep() {
+    /// Checks the end marker `[name]` against the open section, returning the name of the closed section.
+    fn section_end_marker(&mut self, name: &str) -> Option<String> {
+        let closed = match (self.current_section.take(), name.strip_prefix('/')) {
+            (Some(open), None) => open,
+            (Some(open), Some(closed)) if open == closed => open,
+            (open, closed) => {
+                let message = match (open, closed) {
+                    (Some(open), Some(closed)) => {
+                        format!("Cannot close section '{closed}' while '{open}' is open")
+                    }
+                    (_, Some(closed)) => {
+                        format!("Cannot close section '{closed}' while none is open")
+                    }
+                    (_, None) => "Cannot close a section while none is open".to_owned(),
+                };
+                self.add_error(ParserErrorKind::Syntax, &message);
                 return None;
             }
+        };
 
-            if let Some(val) = self.value() {
-                return Some(Element::Entry(key, val));
-            }
-        }
-
-        None
+        Some(closed)
     }
 
-    fn key_name(&mut self) -> Option<String> {
-        self.slice_while(|ch| matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-'))
-            .map(str::to_owned)
-    }
+    fn section_attributes(&mut self) -> Option<Dictionary> {
+        self.cur.next();
+        let mut attributes = Dictionary::new();
 
-    fn value(&mut self) -> Option<Value> {
-        self.whitespace();
-        self.newline();
-        self.whitespace();
+        loop {
+            self.whitespace();
 
-        match self.cur.peek() {
-            Some((_, '"')) => self.finish_string(),
-            Some((_, '[')) => self.finish_array(),
-            Some((_, '{')) => self.finish_dictionary(),
-            Some((_, ch)) if ch.is_ascii_digit() => self.number(),
-            Some((pos, 't')) | Some((pos, 'f')) => {
-                let pos = *pos;
-                self.boolean(pos)
-            }
-            _ => {
-                self.add_error("Cannot read a value");
-                None
+            match self.cur.peek() {
+                Some((_, ')')) => {
+                    self.cur.next();
+                    return Some(attributes);
+                }
+                Some((_, ',')) => {
+                    self.cur.next();
+                }
+                Some(_) => {
+                    let key = match self.key_name() {
+                        Some(key) => key,
+                        None => {
+                            self.add_error(ParserErrorKind::Syntax, "Expected an attribute name");
+                            return None;
+                        }
+                    };
+
+                    if !self.keyval_sep() {
+                        return None;
+                    }
+
+                    self.whitespace();
+                    let value = self.attribute_value()?;
+                    attributes.insert(key, value);
+                }
+                None => {
+                    self.add_error(ParserErrorKind::Syntax, "Cannot finish section attributes");
+                    return None;
+                }
             }
         }
     }
 
-    fn finish_array(&mut self) -> Option<Value> {
-        self.cur.next();
+    /// Reads a value, also accepting a bare word such as `table` as a string.
+    fn attribute_value(&mut self) -> Option<Value> {
+        match self.cur.peek() {
+            Some((_, ch)) if ch.is_ascii_alphabetic() => {
+                let word = self
+                    .slice_while(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.'))
+                    .unwrap_or_default();
+
+                Some(match word {
+                    "true" => Value::Boolean(true),
+                    "false" => Value::Boolean(false),
+                    _ => Value::String(word.to_owned()),
+                })
+            }
+            _ => self.value(),
+        }
+    }
 
-        let mut row = Vec::with_capacity(self.array_capacity);
+    fn entry(&mut self) -> Option<Element> {
+        let key = self.entry_key()?;
+        let value = match (self.bareword_values, self.raw_fallback) {
+            (false, false) => self.value()?,
+            (true, _) => self.value_or_rest_of_line().unwrap_or_else(Value::String),
+            (false, true) => self.value_or_rest_of_line().unwrap_or_else(Value::Raw),
+        };
 
-        loop {
+        if self.trailing_comments {
             self.whitespace();
-
-            if let Some((_, ch)) = self.cur.peek() {
-                match ch {
-                    ']' => {
-                        self.cur.next();
-                        return Some(Value::Array(row));
-                    }
-                    ',' => {
-                        self.cur.next();
-                        continue;
-                    }
-                    _ => match self.value() {
-                        Some(v) => row.push(v),
-                        None => break,
-                    },
-                }
-            } else {
-                self.add_error("Cannot finish an array");
-                break;
+            if let Some(Element::Comment(comment)) = self.comment() {
+                self.keep_trailing_comment(comment);
             }
         }
 
-        None
+        Some(Element::Entry(key, value))
     }
 
-    fn finish_dictionary(&mut self) -> Option<Value> {
-        self.cur.next();
-        let mut map = BTreeMap::new();
+    /// Reads a value, falling back to the rest of the line up to a comment, trimmed, when
+    /// it can't be read or is followed by anything but a comment. On the line of a section
+    /// header, a row may follow the value as well, see `with_inline_sections`.
+    fn value_or_rest_of_line(&mut self) -> Result<Value, String> {
+        let cur = self.cur.clone();
+        let errors = self.errors.len();
+        let inline = self.byte_offset() < self.header_line_end;
 
-        loop {
+        if let Some(value) = self.value() {
             self.whitespace();
+            let input = self.input;
+            let rest = &input[self.byte_offset()..];
+            let is_line_end = match rest.chars().next() {
+                None | Some('\n' | '\r') => true,
+                Some('|') => inline,
+                _ => false,
+            } || self
+                .comment_prefixes
+                .iter()
+                .any(|prefix| rest.starts_with(prefix));
+
+            if is_line_end && self.errors.len() == errors {
+                return Ok(value);
+            }
+        }
 
-            if let Some((_, ch)) = self.cur.peek() {
-                match ch {
-                    '}' => {
-                        self.cur.next();
-                        return Some(Value::Dictionary(map));
-                    }
-                    ',' => {
-                        self.cur.next();
-                        continue;
-                    }
-                    '\n' => {
-                        self.cur.next();
-                        continue;
-                    }
-                    _ => {
-                        match self.entry() {
-                            Some(Element::Entry(k, v)) => map.insert(k, v),
-                            None => break,
-                            _ => panic!("Element::Entry expected"),
-                        };
-                    }
-                }
-            } else {
-                self.add_error("Cannot finish a dictionary");
+        self.cur = cur;
+        self.errors.truncate(errors);
+        self.whitespace();
+
+        let start = self.byte_offset();
+        loop {
+            let rest = self.remaining();
+            let is_end = match rest.chars().next() {
+                None | Some('\n') => true,
+                Some('|') => inline,
+                _ => false,
+            };
+
+            if is_end
+                || self
+                    .comment_prefixes
+                    .iter()
+                    .any(|prefix| rest.starts_with(prefix))
+            {
                 break;
             }
+            self.cur.next();
         }
 
-        None
+        Err(self.input[start..self.byte_offset()].trim_end().to_owned())
     }
 
-    fn number(&mut self) -> Option<Value> {
-        let mut is_float = false;
-        let prefix = self.integer()?;
+    /// Reads the key of an entry and the separator following it.
+    fn entry_key(&mut self) -> Option<String> {
+        let key = self.key_name()?;
 
-        let decimal = if self.eat('.') {
+        if self.control_char() {
+            return None;
+        }
+
+        if self.strict_keys && key.starts_with(|c: char| c.is_ascii_digit()) {
+            self.add_error(ParserErrorKind::Syntax, "Key must not start with a digit");
+            return None;
+        }
+
+        if !self.keyval_sep() {
+            return None;
+        }
+
+        Some(key)
+    }
+
+    fn key_name(&mut self) -> Option<String> {
+        self.slice_while(|ch| matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-'))
+            .map(str::to_owned)
+    }
+
+    /// Reads a value. Arrays, dictionaries and anchors being read are kept on
+    /// an explicit stack rather than the call stack, so deep nesting can't overflow it.
+    fn value(&mut self) -> Option<Value> {
+        let mut stack = Vec::new();
+
+        loop {
+            let value = match self.finish_nested(&mut stack)? {
+                Some(value) => value,
+                None => match self.value_start(stack.len())? {
+                    ValueStart::Value(value) => value,
+                    ValueStart::Nested(nested) => {
+                        stack.push(nested);
+                        continue;
+                    }
+                },
+            };
+
+            // Hand the value to the innermost array or dictionary, resolving anchors on the way.
+            loop {
+                match stack.last_mut() {
+                    None => return Some(value),
+                    Some(Nested::Array(row)) => row.push(value),
+                    Some(Nested::Dictionary(map, key)) => {
+                        map.insert(mem::take(key), value);
+                    }
+                    Some(Nested::Anchor(_)) => {
+                        if let (Some(Nested::Anchor(name)), Some(anchors)) =
+                            (stack.pop(), &mut self.anchors)
+                        {
+                            anchors.insert(name, value.clone());
+                        }
+                        continue;
+                    }
+                }
+
+                break;
+            }
+        }
+    }
+
+    /// Reads a scalar value, or opens an array, dictionary or anchor
+    /// at the given nesting depth.
+    fn value_start(&mut self, depth: usize) -> Option<ValueStart> {
+        self.whitespace();
+        self.newline();
+        self.whitespace();
+
+        let nested = match self.cur.peek() {
+            Some((_, '"')) => return self.finish_string().map(ValueStart::Value),
+            Some((_, ch)) if *ch == self.array_delimiters.0 => {
+                Nested::Array(Vec::with_capacity(self.array_capacity))
+            }
+            Some((_, ch)) if *ch == self.dictionary_delimiters.0 => {
+                Nested::Dictionary(Dictionary::new(), String::new())
+            }
+            Some((_, ch)) if ch.is_ascii_digit() || *ch == '-' => {
+                return self.number().map(ValueStart::Value);
+            }
+            Some((_, '&')) if self.anchors.is_some() => Nested::Anchor(String::new()),
+            Some((_, '*')) if self.anchors.is_some() => {
+                return self.anchor_reference().map(ValueStart::Value);
+            }
+            Some((pos, 't')) | Some((pos, 'f')) => {
+                let pos = *pos;
+                let value = self.boolean(pos);
+                if value.is_none() {
+                    self.add_error(ParserErrorKind::InvalidValue, "Cannot read a value");
+                }
+                return value.map(ValueStart::Value);
+            }
+            _ => {
+                self.add_error(ParserErrorKind::InvalidValue, "Cannot read a value");
+                return None;
+            }
+        };
+
+        if depth >= self.max_depth {
+            self.add_error(
+                ParserErrorKind::NestingTooDeep,
+                "Maximum nesting depth exceeded",
+            );
+            return None;
+        }
+
+        self.cur.next();
+
+        match nested {
+            Nested::Anchor(_) => self.anchor_name().map(Nested::Anchor),
+            nested => Some(nested),
+        }
+        .map(ValueStart::Nested)
+    }
+
+    fn anchor_reference(&mut self) -> Option<Value> {
+        self.cur.next();
+        let name = self.anchor_name()?;
+
+        match self.anchors.as_ref().and_then(|anchors| anchors.get(&name)) {
+            Some(value) => {
+                self.anchor_expansion += count_values(value);
+                if self.anchor_expansion > MAX_ANCHOR_EXPANSION {
+                    self.add_error(ParserErrorKind::Syntax, "Anchor expansion too large");
+                    return None;
+                }
+                Some(value.clone())
+            }
+            None => {
+                self.add_error(ParserErrorKind::Syntax, &format!("Unknown anchor '{name}'"));
+                None
+            }
+        }
+    }
+
+    fn anchor_name(&mut self) -> Option<String> {
+        let name = self.key_name();
+
+        if name.is_none() {
+            self.add_error(ParserErrorKind::Syntax, "Expected an anchor name");
+        }
+
+        name
+    }
+
+    /// Skips separators in the innermost array or dictionary, returning its value
+    /// when it's closed, or `None` when a value follows. For a dictionary the key
+    /// of the following entry is read.
+    fn finish_nested(&mut self, stack: &mut Vec<Nested>) -> Option<Option<Value>> {
+        let (is_array, close) = match stack.last() {
+            Some(Nested::Array(_)) => (true, self.array_delimiters.1),
+            Some(Nested::Dictionary(..)) => (false, self.dictionary_delimiters.1),
+            Some(Nested::Anchor(_)) | None => return Some(None),
+        };
+
+        loop {
+            self.whitespace();
+
+            match self.cur.peek() {
+                Some((_, ch)) if *ch == close => {
+                    self.cur.next();
+                    return match stack.pop() {
+                        Some(Nested::Array(row)) => Some(Some(Value::Array(row))),
+                        Some(Nested::Dictionary(map, _)) => Some(Some(Value::Dictionary(map))),
+                        _ => unreachable!(),
+                    };
+                }
+                Some((_, ',')) => {
+                    self.cur.next();
+                }
+                Some((_, '\n')) if !is_array => {
+                    self.cur.next();
+                }
+                Some(_) if is_array => return Some(None),
+                Some(_) => {
+                    let key = self.entry_key()?;
+                    if let Some(Nested::Dictionary(_, pending)) = stack.last_mut() {
+                        *pending = key;
+                    }
+                    return Some(None);
+                }
+                None if is_array => {
+                    self.add_error(ParserErrorKind::UnterminatedArray, "Cannot finish an array");
+                    return None;
+                }
+                None => {
+                    self.add_error(
+                        ParserErrorKind::UnterminatedDictionary,
+                        "Cannot finish a dictionary",
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        #[cfg(feature = "datetime")]
+        if let Some(value) = self.datetime() {
+            return Some(value);
+        }
+
+        let mut is_float = false;
+        let sign = if self.eat('-') { "-" } else { "" };
+        let prefix = match self.integer() {
+            Some(integer) => sign.to_owned() + &integer,
+            None => {
+                self.add_error(ParserErrorKind::InvalidNumber, "Cannot parse a number");
+                return None;
+            }
+        };
+
+        if self.remaining().starts_with("..") {
+            if !self.ranges {
+                // Otherwise `3..7` would be read as the float `3.` followed by garbage.
+                self.add_error(ParserErrorKind::Syntax, "Unexpected '..' after a number");
+                return None;
+            }
+            return self.range(&prefix);
+        }
+
+        let decimal = if self.eat('.') {
             is_float = true;
             Some(self.integer())?
         } else {
@@ -285,10 +1139,83 @@ impl<'a> Parser<'a> {
             None => prefix,
         };
 
-        if is_float {
+        if self.remaining().starts_with("..") {
+            match self.ranges {
+                true => self.add_error(ParserErrorKind::InvalidRange, "Cannot parse a range"),
+                false => self.add_error(ParserErrorKind::Syntax, "Unexpected '..' after a number"),
+            }
+            return None;
+        }
+
+        if !self.percent && matches!(self.cur.peek(), Some((_, '%'))) {
+            self.add_error(ParserErrorKind::Syntax, "Unexpected '%' after a number");
+            return None;
+        }
+
+        let value = if self.percent && self.eat('%') {
+            input.parse().ok().map(|n: f64| Value::Float(n / 100.0))
+        } else if is_float {
             input.parse().ok().map(Value::Float)
         } else {
-            input.parse().ok().map(Value::Integer)
+            let value = input.parse().ok().map(Value::Integer);
+            #[cfg(feature = "i128")]
+            let value = value.or_else(|| input.parse().ok().map(Value::BigInt));
+            value
+        };
+
+        match value {
+            Some(value) => Some(value),
+            None if self.number_fallback_string => Some(Value::String(input)),
+            None => {
+                self.add_error(ParserErrorKind::InvalidNumber, "Cannot parse a number");
+                None
+            }
+        }
+    }
+
+    /// Reads a timestamp, or a plain date as a string.
+    #[cfg(feature = "datetime")]
+    fn datetime(&mut self) -> Option<Value> {
+        let start = self.cur.peek()?.0;
+        let (value, len) = match datetime::lex(&self.input[start..])? {
+            Lexeme::Date(len) => (
+                Value::String(self.input[start..start + len].to_owned()),
+                len,
+            ),
+            Lexeme::DateTime(datetime, len) => (Value::DateTime(datetime), len),
+        };
+
+        // Both forms are ASCII only.
+        for _ in 0..len {
+            self.cur.next();
+        }
+
+        Some(value)
+    }
+
+    /// Reads the rest of a range following its `start`, from the `..` on.
+    fn range(&mut self, start: &str) -> Option<Value> {
+        self.cur.next();
+        self.cur.next();
+        let inclusive = self.eat('=');
+        let sign = if self.eat('-') { "-" } else { "" };
+        let end = self.integer().map(|end| sign.to_owned() + &end);
+
+        let bounds = match (start.parse(), end.map(|end| end.parse())) {
+            (Ok(start), Some(Ok(end))) if !self.remaining().starts_with('.') => Some((start, end)),
+            _ => None,
+        };
+
+        match bounds {
+            Some((start, end)) => Some(Value::Range {
+                start,
+                end,
+                inclusive,
+            }),
+            None => {
+                self.add_error(ParserErrorKind::InvalidRange, "Cannot parse a range");
+                None
+            }
         }
     }
 
@@ -320,14 +1247,27 @@ impl<'a> Parser<'a> {
     fn finish_string(&mut self) -> Option<Value> {
         self.cur.next();
 
-        self.slice_to_excluding('"')
-            .map(|s| Value::String(replace_escapes(s, true)))
+        let trim_quoted = self.trim_quoted;
+
+        if self.raw_backslash {
+            return self.slice_to_excluding_raw('"').map(|s| {
+                let s = if trim_quoted { s.trim() } else { s };
+                Value::String(s.to_owned())
+            });
+        }
+
+        self.slice_to_excluding('"').map(|s| {
+            let s = if trim_quoted { s.trim() } else { s };
+            Value::String(replace_escapes(s, true))
+        })
     }
 
     fn keyval_sep(&mut self) -> bool {
         self.whitespace();
 
-        if !self.expect('=') {
+        if !self.expect(self.keyval_separator) {
+            let message = format!("Expected '{}' after a key", self.keyval_separator);
+            self.add_error(ParserErrorKind::Syntax, &message);
             return false;
         }
 
@@ -347,7 +1287,10 @@ impl<'a> Parser<'a> {
         loop {
             self.whitespace();
 
-            if self.comment().is_some() {
+            if let Some(Element::Comment(comment)) = self.comment() {
+                if self.trailing_comments {
+                    self.keep_trailing_comment(comment);
+                }
                 break;
             }
 
@@ -359,59 +1302,223 @@ impl<'a> Parser<'a> {
                 break;
             }
 
+            if self.heredocs && self.input[self.byte_offset()..].starts_with("<<") {
+                row.push(Value::String(self.heredoc()?));
+                continue;
+            }
+
             row.push(Value::String(self.cell()));
         }
 
         Some(Element::Row(row))
     }
 
+    fn heredoc(&mut self) -> Option<String> {
+        let input = self.input;
+        self.cur.next();
+        self.cur.next();
+
+        let marker_start = self.byte_offset();
+        if self
+            .slice_while(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            .is_none()
+        {
+            self.add_error(
+                ParserErrorKind::Syntax,
+                "Expected a heredoc marker after '<<'",
+            );
+            return None;
+        }
+        let marker = &input[marker_start..self.byte_offset()];
+
+        self.whitespace();
+        if !self.newline() {
+            self.add_error(
+                ParserErrorKind::Syntax,
+                "Expected a newline after the heredoc marker",
+            );
+            return None;
+        }
+
+        let start = self.byte_offset();
+
+        loop {
+            let line_start = self.byte_offset();
+            self.whitespace();
+
+            let rest = &input[self.byte_offset()..];
+            if let Some(after) = rest.strip_prefix(marker) {
+                let after = after.trim_start_matches(&[' ', '\t'][..]);
+
+                if after.is_empty() || after.starts_with(&['|', '\n', '\r'][..]) {
+                    for _ in marker.chars() {
+                        self.cur.next();
+                    }
+                    self.whitespace();
+                    self.eat('|');
+
+                    let content = &input[start..line_start];
+                    let content = content.strip_suffix('\n').unwrap_or(content);
+                    let content = content.strip_suffix('\r').unwrap_or(content);
+                    return Some(content.to_owned());
+                }
+            }
+
+            if self.cur.by_ref().find(|&(_, c)| c == '\n').is_none() {
+                self.add_error(ParserErrorKind::Syntax, "Unterminated heredoc");
+                return None;
+            }
+        }
+    }
+
     fn cell(&mut self) -> String {
         self.whitespace();
 
-        replace_escapes(
-            self.slice_to_excluding('|')
-                .map(str::trim_end)
-                .unwrap_or_default(),
-            false,
-        )
+        let start = self.byte_offset();
+        let cell = self.slice_to_excluding('|').unwrap_or_default();
+        let trimmed = cell.trim_end();
+        // An escaped whitespace character ends the cell, it isn't trimmed.
+        let escapes = trimmed.chars().rev().take_while(|&c| c == '\\').count();
+        let cell = match cell[trimmed.len()..].chars().next() {
+            Some(c) if escapes % 2 == 1 => &cell[..trimmed.len() + c.len_utf8()],
+            _ => trimmed,
+        };
+        self.reject_control_chars(cell, start);
+
+        replace_escapes(cell, false)
     }
 
     pub fn read(&mut self) -> Option<BTreeMap<String, Section>> {
+        self.read_with_stats().map(|(map, _)| map)
+    }
+
+    /// Same as [`Parser::read`], but additionally returns the [`Warning`]s recorded
+    /// by opt-in lints such as [`Parser::with_indent_lint`].
+    ///
+    /// Unlike errors, warnings don't make the read fail.
+    pub fn read_with_warnings(&mut self) -> Option<(BTreeMap<String, Section>, Vec<Warning>)> {
+        self.read().map(|map| (map, mem::take(&mut self.warnings)))
+    }
+
+    /// Same as [`Parser::read`], but additionally returns [`ParseStats`] about the parsed input.
+    pub fn read_with_stats(&mut self) -> Option<(BTreeMap<String, Section>, ParseStats)> {
         let mut map = BTreeMap::new();
         let mut section = Section::with_capacity(self.section_capacity);
         let mut name = None;
+        let mut start = 0;
+        let mut stats = ParseStats::default();
+        // Keys of the current section whose values were collected into an array.
+        let mut repeated_keys = BTreeSet::new();
 
         while let Some(el) = self.next() {
             match el {
                 Element::Section(n) => {
-                    if let Some(name) = name {
-                        map.insert(name, section);
+                    stats.sections += 1;
+                    self.set_span(&mut section, start);
+                    match name {
+                        Some(name) => {
+                            map.insert(name, section);
+                        }
+                        None if self.keeps_filtered_root() => {
+                            map.insert(Section::DEFAULT_NAME.to_owned(), section);
+                        }
+                        None => (),
                     }
                     name = Some(n);
                     section = Section::with_capacity(self.section_capacity);
+                    section.attributes = mem::take(&mut self.section_attributes);
+                    start = self.section_start;
+                    repeated_keys.clear();
+                }
+                Element::Row(row) => {
+                    stats.rows += 1;
+                    if let Some(comment) = self.trailing_comment.take() {
+                        stats.comments += 1;
+                        section.row_comments.insert(section.rows.len(), comment);
+                    }
+                    section.rows.push(row);
+                }
+                Element::Entry(key, value) if self.repeated_keys_as_array => {
+                    stats.entries += 1;
+                    if let Some(comment) = self.trailing_comment.take() {
+                        stats.comments += 1;
+                        section.entry_comments.insert(key.clone(), comment);
+                    }
+                    match section.dictionary.get_mut(&key) {
+                        Some(Value::Array(values)) if repeated_keys.contains(&key) => {
+                            values.push(value)
+                        }
+                        Some(first) => {
+                            *first = Value::Array(vec![
+                                mem::replace(first, Value::Array(Row::new())),
+                                value,
+                            ]);
+                            repeated_keys.insert(key);
+                        }
+                        None => {
+                            section.dictionary.insert(key, value);
+                        }
+                    }
                 }
-                Element::Row(row) => section.rows.push(row),
                 Element::Entry(key, value) => {
+                    stats.entries += 1;
+                    if let Some(comment) = self.trailing_comment.take() {
+                        stats.comments += 1;
+                        section.entry_comments.insert(key.clone(), comment);
+                    }
                     section.dictionary.insert(key, value);
                 }
-                _ => continue,
+                Element::Comment(_) | Element::TrailingComment(_) => stats.comments += 1,
+                Element::SectionEnd(_) => {
+                    self.set_span(&mut section, start);
+                    if let Some(name) = name {
+                        map.insert(name, section);
+                    }
+
+                    name = match self.accepted_sections.is_none() || self.keeps_filtered_root() {
+                        true => Some(Section::DEFAULT_NAME.to_owned()),
+                        false => None,
+                    };
+                    section = map
+                        .remove(Section::DEFAULT_NAME)
+                        .unwrap_or_else(|| Section::with_capacity(self.section_capacity));
+                    start = self.byte_offset();
+                    repeated_keys.clear();
+                }
             }
         }
 
+        stats.bytes = self.byte_offset();
+        self.set_span(&mut section, start);
+
         match name {
             Some(name) => {
                 map.insert(name, section);
             }
-            None if self.accepted_sections.is_none() => {
-                map.insert("root".to_string(), section);
+            None if self.accepted_sections.is_none() || self.keeps_filtered_root() => {
+                map.insert(Section::DEFAULT_NAME.to_owned(), section);
             }
             _ => (),
         }
 
-        if !self.errors.is_empty() {
+        if self.errors.len() > self.skipped_errors {
             None
         } else {
-            Some(map)
+            Some((map, stats))
+        }
+    }
+
+    fn keeps_filtered_root(&self) -> bool {
+        self.root_in_filter && self.accepted_sections.is_some()
+    }
+
+    fn set_span(&mut self, section: &mut Section, start: usize) {
+        if self.spans {
+            let end = match self.section_end.take() {
+                Some(end) => end,
+                None => self.byte_offset(),
+            };
+            section.span = Some((start, end));
         }
     }
 
@@ -437,28 +1544,31 @@ impl<'a> Parser<'a> {
     fn slice_to_including(&mut self, ch: char) -> Option<&str> {
         self.cur.next().map(|(start, c)| {
             if c == ch {
-                &self.input[start..=start]
+                &self.input[start..start + ch.len_utf8()]
             } else {
                 self.cur
                     .find(|(_, c)| *c == ch)
-                    .map_or(&self.input[start..], |(end, _)| &self.input[start..=end])
+                    .map_or(&self.input[start..], |(end, _)| {
+                        &self.input[start..end + ch.len_utf8()]
+                    })
             }
         })
     }
 
-    fn slice_to_excluding(&mut self, ch: char) -> Option<&str> {
+    fn slice_to_excluding(&mut self, ch: char) -> Option<&'a str> {
         self.cur.next().map(|(start, c)| {
             if c == ch {
                 ""
             } else {
-                let mut prev_element = c;
+                // A backslash escapes the next char, unless it's escaped itself.
+                let mut escaping = c == '\\';
 
                 for (i, cur_ch) in self.cur.by_ref() {
-                    if cur_ch == ch && prev_element != '\\' {
+                    if cur_ch == ch && !escaping {
                         return &self.input[start..i];
                     }
 
-                    prev_element = cur_ch;
+                    escaping = cur_ch == '\\' && !escaping;
                 }
 
                 &self.input[start..]
@@ -466,6 +1576,23 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// Like `slice_to_excluding`, but without treating backslashes as escapes.
+    fn slice_to_excluding_raw(&mut self, ch: char) -> Option<&'a str> {
+        self.cur.next().map(|(start, c)| {
+            if c == ch {
+                return "";
+            }
+
+            for (i, cur_ch) in self.cur.by_ref() {
+                if cur_ch == ch {
+                    return &self.input[start..i];
+                }
+            }
+
+            &self.input[start..]
+        })
+    }
+
     fn slice_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<&str> {
         self.cur.peek().cloned().and_then(|(start, c)| {
             if !predicate(c) {
@@ -486,27 +1613,141 @@ impl<'a> Parser<'a> {
         })
     }
 
-    fn add_error(&mut self, message: &str) {
+    /// Returns the comment lines at the start of the document, up to the first blank
+    /// or other line, with the comment prefixes removed and joined by newlines.
+    ///
+    /// Blank lines before the block are skipped. Returns `None` unless
+    /// `with_preamble` is enabled and the document starts with a comment.
+    pub fn preamble(&self) -> Option<String> {
+        if !self.preamble {
+            return None;
+        }
+
+        let lines: Vec<&str> = self
+            .input
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .map_while(|line| {
+                let line = line.trim_start();
+                self.comment_prefixes
+                    .iter()
+                    .find(|prefix| line.starts_with(**prefix))
+                    .map(|prefix| &line[prefix.len()..])
+            })
+            .collect();
+
+        match lines.is_empty() {
+            true => None,
+            false => Some(lines.join("\n")),
+        }
+    }
+
+    /// Returns the position of the parser in the input, in bytes.
+    ///
+    /// When consuming the parser as an `Iterator`, this is the position right after
+    /// the last returned element.
+    pub fn byte_offset(&self) -> usize {
+        self.cur
+            .clone()
+            .next()
+            .map_or(self.input.len(), |(pos, _)| pos)
+    }
+
+    /// Returns the input not consumed yet, starting at `byte_offset`.
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.byte_offset()..]
+    }
+
+    /// Records an error if the next character is a control character, see `reject_control_chars`.
+    fn control_char(&mut self) -> bool {
+        match self.cur.peek() {
+            Some(&(pos, c)) => {
+                let input = self.input;
+                self.reject_control_chars(&input[pos..pos + c.len_utf8()], pos)
+            }
+            None => false,
+        }
+    }
+
+    /// Records an error for the first control character in `s`, found at `offset` in the input.
+    /// Control characters other than tabs and line breaks may only appear in quoted strings.
+    fn reject_control_chars(&mut self, s: &str, offset: usize) -> bool {
+        match s.char_indices().find(|&(_, c)| is_disallowed_control(c)) {
+            Some((i, c)) => {
+                let desc = format!("Unexpected control character U+{:04X}", c as u32);
+                self.push_error(
+                    offset + i,
+                    offset + i + c.len_utf8(),
+                    ParserErrorKind::Syntax,
+                    desc,
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn add_error(&mut self, kind: ParserErrorKind, message: &str) {
         let mut it = self.cur.clone();
         let lo = it.next().map(|p| p.0).unwrap_or(self.input.len());
         let hi = it.next().map(|p| p.0).unwrap_or(self.input.len());
 
+        self.push_error(lo, hi, kind, message.to_owned());
+    }
+
+    fn push_error(&mut self, lo: usize, hi: usize, kind: ParserErrorKind, desc: String) {
+        let (line, column) = line_column(self.input, lo);
+
         self.errors.push(ParserError {
             lo,
             hi,
-            desc: message.to_owned(),
+            desc,
+            kind,
+            section: self.current_section.clone(),
+            line,
+            column,
         });
     }
 }
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct ParserError {
     pub lo: usize,
     pub hi: usize,
     pub desc: String,
+    pub kind: ParserErrorKind,
+    /// Name of the section being read when the error occurred, `None` before the
+    /// first header and after an end marker.
+    pub section: Option<String>,
+    /// 1-based line of `lo` in the input.
+    pub line: usize,
+    /// 1-based column of `lo` in the input, counted in chars.
+    pub column: usize,
 }
 
-impl error::Error for ParserError {
+/// What went wrong in a `ParserError`, for telling errors apart without matching `desc`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParserErrorKind {
+    /// An array is missing its closing bracket.
+    UnterminatedArray,
+    /// A dictionary is missing its closing brace.
+    UnterminatedDictionary,
+    /// Nothing valid could be read where a value was expected.
+    InvalidValue,
+    /// A number literal is malformed or out of range.
+    InvalidNumber,
+    /// A range literal is malformed or out of range.
+    InvalidRange,
+    /// Values are nested deeper than `Parser::with_max_depth` allows.
+    NestingTooDeep,
+    /// Any other malformed input.
+    Syntax,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParserError {
     fn description(&self) -> &str {
         "error parsing Ion"
     }
@@ -518,28 +1759,131 @@ impl fmt::Display for ParserError {
     }
 }
 
-fn replace_escapes(s: &str, escape_quote: bool) -> String {
-    let mut result = String::new();
-    let mut escaping = false;
-    for c in s.chars() {
-        match (escaping, c) {
-            (false, '\\') => {
-                escaping = true;
-                continue;
-            }
-            (false, c) => result.push(c),
+/// How to go on after a parser error, see `Parser::with_on_error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Stops reading, so that `read` fails.
+    Abort,
+    /// Drops the rest of the line and goes on with the next one.
+    SkipLine,
+    /// Drops the rest of the section and goes on with the next section header.
+    SkipSection,
+}
 
-            (true, 'n') => result.push('\n'),
-            (true, 't') => result.push('\t'),
-            (true, '\\' | '|') => result.push(c),
-            (true, '"') if escape_quote => result.push(c),
-            (true, c) => {
-                // When an unknown escape is encountered, print it as is e.g. \a -> \a
-                result.push('\\');
-                result.push(c);
-            }
-        }
-        escaping = false;
+/// A suspicious but valid construct found while parsing, see `Parser::read_with_warnings`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub lo: usize,
+    pub hi: usize,
+    pub desc: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+fn is_disallowed_control(c: char) -> bool {
+    c.is_control() && !matches!(c, '\t' | '\n' | '\r')
+}
+
+/// Parses `text` holding a single value, such as `42`, `"hello"` or `[1, 2]`,
+/// with the same semantics as entry values.
+///
+/// Surrounding whitespace is allowed, any other content after the value is an error.
+pub fn parse_value(text: &str) -> Result<Value, IonError> {
+    let mut parser = Parser::new(text);
+    let value = parser.value();
+
+    loop {
+        parser.whitespace();
+        if !parser.newline() {
+            break;
+        }
+    }
+
+    if parser.cur.peek().is_some() && parser.errors.is_empty() {
+        parser.add_error(
+            ParserErrorKind::Syntax,
+            "Unexpected content after the value",
+        );
+    }
+
+    match value {
+        Some(value) if parser.errors.is_empty() => Ok(value),
+        _ => {
+            if parser.errors.is_empty() {
+                parser.add_error(ParserErrorKind::InvalidValue, "Cannot read a value");
+            }
+            Err(IonError::ParserErrors(parser.errors))
+        }
+    }
+}
+
+/// How many values anchor references may copy in total, see `Parser::with_anchors`.
+const MAX_ANCHOR_EXPANSION: usize = 1 << 16;
+
+/// Counts `value` and all values nested in it, without recursing.
+fn count_values(value: &Value) -> usize {
+    let mut count = 0;
+    let mut stack = vec![value];
+
+    while let Some(value) = stack.pop() {
+        count += 1;
+        match value {
+            Value::Array(values) => stack.extend(values),
+            Value::Dictionary(values) => stack.extend(values.values()),
+            _ => (),
+        }
+    }
+
+    count
+}
+
+/// Recognizes `s` as a single integer, float or boolean literal, the same way entry values are read.
+pub(crate) fn parse_scalar(s: &str) -> Option<Value> {
+    let mut parser = Parser::new(s);
+
+    let value = match parser.cur.peek() {
+        Some((_, ch)) if ch.is_ascii_digit() || *ch == '-' => parser.number(),
+        Some((pos, 't')) | Some((pos, 'f')) => {
+            let pos = *pos;
+            parser.boolean(pos)
+        }
+        _ => None,
+    }?;
+
+    match parser.cur.peek() {
+        Some(_) => None,
+        None => Some(value),
+    }
+}
+
+fn replace_escapes(s: &str, escape_quote: bool) -> String {
+    let mut result = String::new();
+    let mut escaping = false;
+    for c in s.chars() {
+        match (escaping, c) {
+            (false, '\\') => {
+                escaping = true;
+                continue;
+            }
+            (false, c) => result.push(c),
+
+            (true, 'n') => result.push('\n'),
+            (true, 't') => result.push('\t'),
+            (true, '\\' | '|') => result.push(c),
+            (true, '"') if escape_quote => result.push(c),
+            // Escapes keeping a cell from starting a comment or being trimmed.
+            (true, c) if !escape_quote && (c == '#' || c.is_whitespace()) => result.push(c),
+            (true, c) => {
+                // When an unknown escape is encountered, print it as is e.g. \a -> \a
+                result.push('\\');
+                result.push(c);
+            }
+        }
+        escaping = false;
     }
 
     // handle '\\' as last char in sequence
@@ -553,9 +1897,36 @@ fn replace_escapes(s: &str, escape_quote: bool) -> String {
 #[cfg(test)]
 mod tests {
     use super::Element::{self, Comment, Entry, Row};
-    use crate::{Dictionary, Parser, Section, Value};
+    use crate::{Dictionary, ErrorAction, ParseStats, Parser, Section, Value};
+    use quickcheck_macros::quickcheck;
     use std::collections::BTreeMap;
 
+    const MULTI_SECTION: &str = r#"
+                [dict]
+                first = "first"
+                # comment
+                second ="another"
+                whitespace = "  "
+                empty = ""
+                some_bool = true
+
+                ary = [ "col1", 2,"col3", false]
+
+                [table]
+
+                |abc|def|
+                |---|---|
+                |one|two|
+                # comment
+                |  1| 2 |
+                |  2| 3 |
+
+                [three]
+                a=1
+                B=2
+                | this |
+            "#;
+
     #[test]
     fn finish_string() {
         let mut p = Parser::new("\"foObar\"");
@@ -569,53 +1940,77 @@ mod tests {
 
         let mut p = Parser::new("");
         assert_eq!(None, p.finish_string());
+
+        let mut p = Parser::new("\"  foO bar \"");
+        assert_eq!(Some("  foO bar "), p.finish_string().unwrap().as_str());
+    }
+
+    #[test]
+    fn finish_string_with_trim_quoted() {
+        let mut p = Parser::new("\"  foO bar \"").with_trim_quoted(true);
+        assert_eq!(Some("foO bar"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new("\"  \"").with_trim_quoted(true);
+        assert_eq!(Some(""), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new("\"\\n x\\t \"").with_trim_quoted(true);
+        assert_eq!(Some("\n x\t"), p.finish_string().unwrap().as_str());
+    }
+
+    #[test]
+    fn finish_string_with_raw_backslash() {
+        let mut p = Parser::new(r#""C:\temp\new""#);
+        assert_eq!(Some("C:\temp\new"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(r#""C:\temp\new""#).with_raw_backslash(true);
+        assert_eq!(Some(r"C:\temp\new"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(r#""C:\dir\" x"#).with_raw_backslash(true);
+        assert_eq!(Some(r"C:\dir\"), p.finish_string().unwrap().as_str());
     }
 
     #[test]
     fn finish_array() {
         let mut p = Parser::new("[\"a\"");
-        assert_eq!(None, p.finish_array());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("[");
-        assert_eq!(None, p.finish_array());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("[]");
-        assert_eq!(Some(Value::Array(vec![])), p.finish_array());
+        assert_eq!(Some(Value::Array(vec![])), p.value());
 
         let mut p = Parser::new("[\"a\"]");
-        assert_eq!(Some(Value::new_string_array("a")), p.finish_array());
+        assert_eq!(Some(Value::new_string_array("a")), p.value());
     }
 
     #[test]
     fn finish_dictionary() {
         let mut p = Parser::new("{");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("{ foo");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("{ foo = ");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("{ foo = \"bar\"");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("{ foo = [\"bar\"");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("{ foo = [\"bar\"]");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.value());
 
         let mut p = Parser::new("{}");
-        assert_eq!(
-            Some(Value::Dictionary(Dictionary::new())),
-            p.finish_dictionary()
-        );
+        assert_eq!(Some(Value::Dictionary(Dictionary::new())), p.value());
 
         let mut p = Parser::new("{ foo = [\"bar\"] }");
         assert_eq!(
             "{ foo = [ \"bar\" ] }",
-            p.finish_dictionary().map(|d| format!("{d:#}")).unwrap()
+            p.value().map(|d| format!("{d:#}")).unwrap()
         );
     }
 
@@ -645,6 +2040,42 @@ mod tests {
         assert_eq!(Some((4, 'b')), p.cur.next());
     }
 
+    #[test]
+    fn slice_to_inc_multibyte() {
+        let mut p = Parser::new("éa");
+        assert_eq!(Some("é"), p.slice_to_including('é'));
+        assert_eq!(Some((2, 'a')), p.cur.next());
+
+        let mut p = Parser::new("a😀b");
+        assert_eq!(Some("a😀"), p.slice_to_including('😀'));
+        assert_eq!(Some((5, 'b')), p.cur.next());
+    }
+
+    #[test]
+    fn multibyte_input_does_not_panic() {
+        let inputs = [
+            "😀",
+            "[😀]\nkey = \"😀\"\n| 😀 | é |\n# 😀",
+            "key = t😀",
+            "key = f\u{301}",
+            "key = 1😀",
+            "key = \"\\😀\"",
+            "| a\u{301}\u{302} | \\😀 |",
+            "[a\u{301}",
+            "#\u{301}",
+        ];
+
+        for input in inputs {
+            let _ = Parser::new(input).read();
+        }
+    }
+
+    #[quickcheck]
+    fn arbitrary_input_does_not_panic(input: String) {
+        let _ = Parser::new(&input).read();
+        let _ = Parser::new(&format!("[A]\nkey = {input}\n| {input} |")).read();
+    }
+
     #[test]
     fn slice_while() {
         let mut p = Parser::new("foObar");
@@ -658,33 +2089,7 @@ mod tests {
 
     #[test]
     fn parse() {
-        let raw = r#"
-                [dict]
-                first = "first"
-                # comment
-                second ="another"
-                whitespace = "  "
-                empty = ""
-                some_bool = true
-
-                ary = [ "col1", 2,"col3", false]
-
-                [table]
-
-                |abc|def|
-                |---|---|
-                |one|two|
-                # comment
-                |  1| 2 |
-                |  2| 3 |
-
-                [three]
-                a=1
-                B=2
-                | this |
-            "#;
-
-        let mut p = Parser::new(raw);
+        let mut p = Parser::new(MULTI_SECTION);
 
         assert_eq!(Some(Element::Section("dict".to_owned())), p.next());
         assert_eq!(
@@ -772,12 +2177,115 @@ mod tests {
         assert_eq!(None, p.next());
     }
 
+    #[test]
+    fn remaining() {
+        let mut p = Parser::new("[FOO]\nkey = 1\n[BAR]\nkey = 2\n");
+        assert_eq!(0, p.byte_offset());
+
+        assert_eq!(Some(Element::Section("FOO".to_owned())), p.next());
+        assert_eq!(
+            Some(Element::Entry("key".to_owned(), Value::Integer(1))),
+            p.next()
+        );
+
+        assert_eq!(13, p.byte_offset());
+        assert_eq!("\n[BAR]\nkey = 2\n", p.remaining());
+
+        assert_eq!(2, p.count());
+    }
+
+    #[test]
+    fn preamble() {
+        let raw = "\n  # Hotel definitions\n  # generated\n\n# not part of it\n[FOO]\n";
+
+        assert_eq!(None, Parser::new(raw).preamble());
+
+        let p = Parser::new(raw).with_preamble(true);
+        assert_eq!(
+            Some(" Hotel definitions\n generated"),
+            p.preamble().as_deref()
+        );
+
+        let p = Parser::new("[FOO]\n# comment\n").with_preamble(true);
+        assert_eq!(None, p.preamble());
+    }
+
+    #[test]
+    fn read_with_stats() {
+        let mut p = Parser::new(MULTI_SECTION);
+
+        let (sections, stats) = p.read_with_stats().unwrap();
+
+        assert_eq!(3, sections.len());
+        assert_eq!(
+            ParseStats {
+                sections: 3,
+                entries: 8,
+                rows: 6,
+                comments: 2,
+                bytes: MULTI_SECTION.len(),
+            },
+            stats
+        );
+    }
+
     #[test]
     fn display() {
         let ary = Value::Array(vec![Value::Integer(1), Value::String("foo".to_owned())]);
         assert_eq!(format!("{ary:#}"), "[ 1, \"foo\" ]");
     }
 
+    #[test]
+    fn display_element() {
+        let elements = vec![
+            Element::Section("FOO".to_owned()),
+            Entry(
+                "key".to_owned(),
+                Value::Array(vec![Value::Integer(1), Value::String("a b".to_owned())]),
+            ),
+            Row(vec![
+                Value::String("a".to_owned()),
+                Value::String("b".to_owned()),
+            ]),
+            Comment(" comment".to_owned()),
+        ];
+
+        for element in elements {
+            let raw = element.to_string();
+            assert_eq!(Some(element), Parser::new(&raw).next(), "{raw}");
+        }
+
+        assert_eq!("[FOO]", Element::Section("FOO".to_owned()).to_string());
+        assert_eq!(
+            "| a | b |",
+            Row(vec![Value::new_string("a"), Value::new_string("b")]).to_string()
+        );
+        assert_eq!("# comment", Comment(" comment\n".to_owned()).to_string());
+    }
+
+    #[test]
+    fn parse_value() {
+        assert_eq!(Value::Integer(42), super::parse_value("42").unwrap());
+        assert_eq!(
+            Value::new_string("hello"),
+            super::parse_value(" \"hello\"\n").unwrap()
+        );
+        assert_eq!(
+            Value::Array(vec![Value::Integer(1), Value::new_string("a")]),
+            super::parse_value("[1, \"a\"]").unwrap()
+        );
+
+        let err = super::parse_value("42 garbage").unwrap_err();
+        assert_eq!(
+            "ParserErrors([ParserError { lo: 3, hi: 4, desc: \"Unexpected content after the value\", kind: Syntax, section: None, line: 1, column: 4 }])",
+            err.to_string()
+        );
+
+        let err = super::parse_value("fast").unwrap_err();
+        assert!(err.is_parse_error());
+        assert!(super::parse_value("").is_err());
+    }
+
     #[test]
     fn replace_escapes() {
         assert_eq!("a b", super::replace_escapes("a b", true));
@@ -823,101 +2331,984 @@ mod tests {
                     }
                 }
 
-                mod and_root_section_has_dictionary_with_array {
+                mod and_root_section_has_dictionary_with_array {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_dictionary() {
+                        let raw = r#"
+                            arr = ["WAW", "WRO"]
+                        "#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        let array = vec![
+                            Value::String("WAW".to_owned()),
+                            Value::String("WRO".to_owned()),
+                        ];
+                        section
+                            .dictionary
+                            .insert("arr".to_owned(), Value::Array(array));
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_root_section_has_dictionary_with_dictionary {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_dictionary() {
+                        let raw = r#"
+                            ndict = { foo = "bar" }
+                        "#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        let mut dict = BTreeMap::new();
+                        dict.insert("foo".to_owned(), Value::String("bar".to_owned()));
+                        section
+                            .dictionary
+                            .insert("ndict".to_owned(), Value::Dictionary(dict));
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_root_section_has_dictionary_with_dictionary_with_new_lines {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_dictionary() {
+                        let raw = r#"
+                            R75042 = {
+                            view = "SV"
+                            loc  = ["M", "B"]
+                            dist = { beach_km = 4.1 }
+                        }"#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut sect = Section::new();
+                        let mut dict = BTreeMap::new();
+                        dict.insert("view".to_owned(), Value::String("SV".to_owned()));
+                        let array =
+                            vec![Value::String("M".to_owned()), Value::String("B".to_owned())];
+                        dict.insert("loc".to_owned(), Value::Array(array));
+                        let mut dict_dict = BTreeMap::new();
+                        dict_dict.insert("beach_km".to_owned(), Value::Float(4.1));
+                        dict.insert("dist".to_owned(), Value::Dictionary(dict_dict));
+                        sect.dictionary
+                            .insert("R75042".to_owned(), Value::Dictionary(dict));
+                        expected.insert("root".to_owned(), sect);
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_root_section_has_dictionary_with_dictionary_with_no_value {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_error() {
+                        let raw = r#"
+                            key =
+                        "#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read();
+
+                        assert_eq!(None, actual);
+                    }
+                }
+
+                #[cfg(feature = "i128")]
+                mod and_root_section_has_integer_fitting_only_i128 {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_big_int() {
+                        let raw = r#"
+                            id = 170141183460469231731687303715884105727
+                        "#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read().unwrap();
+
+                        let id = actual["root"].get("id").unwrap();
+                        assert_eq!(&Value::BigInt(i128::MAX), id);
+                        assert_eq!(Some(i128::MAX), id.as_i128());
+                        assert_eq!(None, id.as_integer());
+                        assert_eq!("170141183460469231731687303715884105727", id.to_string());
+                    }
+                }
+
+                mod and_root_section_has_integer_overflowing_i64 {
+                    use super::*;
+
+                    // Exceeds `i128` as well, so it doesn't fit with the `i128` feature either.
+                    const RAW: &str = r#"
+                        id = 999999999999999999999999999999999999999999
+                    "#;
+
+                    #[test]
+                    fn then_returns_error() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot parse a number", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_string_with_number_fallback_string() {
+                        let mut p = Parser::new(RAW).with_number_fallback_string(true);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        section.dictionary.insert(
+                            "id".to_owned(),
+                            Value::String("999999999999999999999999999999999999999999".to_owned()),
+                        );
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_root_section_has_negative_numbers {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_negative_integer_and_float() {
+                        let raw = r#"
+                            int = -1
+                            float = -0.5
+                        "#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some(&Value::Integer(-1)), actual["root"].get("int"));
+                        assert_eq!(Some(&Value::Float(-0.5)), actual["root"].get("float"));
+                    }
+
+                    #[test]
+                    fn then_returns_error_on_bare_minus() {
+                        let mut p = Parser::new("key = -");
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot parse a number", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_keeps_negative_cell_as_string() {
+                        let mut p = Parser::new("| -1 |");
+
+                        let actual = p.read().unwrap();
+
+                        let expected = vec![vec![Value::String("-1".to_owned())]];
+                        assert_eq!(expected, actual["root"].rows);
+                    }
+                }
+
+                mod and_root_section_has_unknown_value {
+                    use super::*;
+
+                    const RAW: &str = "
+                        key = 1
+                        when = @today + 1d
+                        list = [1, ?]
+                        other = \"value\"
+                    ";
+
+                    #[test]
+                    fn then_returns_error() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot read a value", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_raw_value_with_raw_fallback() {
+                        let mut p = Parser::new(RAW).with_raw_fallback(true);
+
+                        let actual = p.read().unwrap();
+
+                        let root = &actual["root"];
+                        assert_eq!(Some(&Value::Integer(1)), root.get("key"));
+                        assert_eq!(
+                            Some(&Value::Raw("@today + 1d".to_owned())),
+                            root.get("when")
+                        );
+                        assert_eq!(Some(&Value::Raw("[1, ?]".to_owned())), root.get("list"));
+                        assert_eq!(Some("value"), root.get("other").and_then(Value::as_str));
+                        assert!(p.errors.is_empty());
+
+                        let written = format!("[FOO]\n{}", root);
+                        let mut p = Parser::new(&written).with_raw_fallback(true);
+                        assert_eq!(Some(root), p.read().unwrap().get("FOO"));
+                    }
+
+                    #[test]
+                    fn then_ends_raw_value_at_a_comment() {
+                        let raw = "x = @x # comment\ny = @y//other\n";
+                        let mut p = Parser::new(raw)
+                            .with_raw_fallback(true)
+                            .with_comment_prefixes(&["#", "//"])
+                            .with_trailing_comments(true);
+
+                        let actual = p.read().unwrap();
+
+                        let root = &actual["root"];
+                        assert_eq!(Some(&Value::Raw("@x".to_owned())), root.get("x"));
+                        assert_eq!(Some(&Value::Raw("@y".to_owned())), root.get("y"));
+                        assert_eq!(
+                            Some(" comment"),
+                            root.entry_comments.get("x").map(String::as_str)
+                        );
+                    }
+
+                    #[test]
+                    fn then_keeps_rows_after_raw_value() {
+                        let raw = "[FOO] key = 1 | a | b |\n[BAR] when = @today | c |\n";
+                        let mut p = Parser::new(raw)
+                            .with_raw_fallback(true)
+                            .with_inline_sections(true);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some(&Value::Integer(1)), actual["FOO"].get("key"));
+                        assert_eq!(
+                            vec![vec![Value::new_string("a"), Value::new_string("b")]],
+                            actual["FOO"].rows
+                        );
+                        assert_eq!(
+                            Some(&Value::Raw("@today".to_owned())),
+                            actual["BAR"].get("when")
+                        );
+                        assert_eq!(vec![vec![Value::new_string("c")]], actual["BAR"].rows);
+                    }
+                }
+
+                mod and_root_section_has_bareword_values {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        mode = read-only
+                        level = high
+                        flag = fast
+                        enabled = true
+                        name = "quoted"
+                    "#;
+
+                    #[test]
+                    fn then_returns_error() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot read a value", p.errors[0].desc);
+
+                        let mut p = Parser::new("flag = fast");
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot read a value", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_strings_with_bareword_values() {
+                        let mut p = Parser::new(RAW).with_bareword_values(true);
+
+                        let actual = p.read().unwrap();
+
+                        let root = &actual["root"];
+                        assert_eq!(Some("read-only"), root.get("mode").and_then(Value::as_str));
+                        assert_eq!(Some("high"), root.get("level").and_then(Value::as_str));
+                        assert_eq!(Some("fast"), root.get("flag").and_then(Value::as_str));
+                        assert_eq!(Some(true), root.get("enabled").and_then(Value::as_boolean));
+                        assert_eq!(Some("quoted"), root.get("name").and_then(Value::as_str));
+                    }
+
+                    #[test]
+                    fn then_ends_bareword_values_at_a_comment() {
+                        let raw = "mode = read-only # comment\nlevel = high// other\n";
+                        let mut p = Parser::new(raw)
+                            .with_bareword_values(true)
+                            .with_comment_prefixes(&["#", "//"])
+                            .with_trailing_comments(true);
+
+                        let actual = p.read().unwrap();
+
+                        let root = &actual["root"];
+                        assert_eq!(Some("read-only"), root.get("mode").and_then(Value::as_str));
+                        assert_eq!(Some("high"), root.get("level").and_then(Value::as_str));
+                        assert_eq!(
+                            Some(" comment"),
+                            root.entry_comments.get("mode").map(String::as_str)
+                        );
+                    }
+
+                    #[test]
+                    fn then_keeps_pipes_in_bareword_values() {
+                        let raw = "[FOO]\ncmd = ls | grep x\n[BAR] cmd = ls | a |\n";
+                        let mut p = Parser::new(raw)
+                            .with_bareword_values(true)
+                            .with_inline_sections(true);
+
+                        let actual = p.read().unwrap();
+
+                        assert!(actual["FOO"].rows.is_empty());
+                        assert_eq!(
+                            Some("ls | grep x"),
+                            actual["FOO"].get("cmd").and_then(Value::as_str)
+                        );
+                        assert_eq!(Some("ls"), actual["BAR"].get("cmd").and_then(Value::as_str));
+                        assert_eq!(vec![vec![Value::new_string("a")]], actual["BAR"].rows);
+
+                        let mut p = Parser::new(raw).with_raw_fallback(true);
+                        let actual = p.read().unwrap();
+                        assert_eq!(
+                            Some(&Value::Raw("ls | grep x".to_owned())),
+                            actual["FOO"].get("cmd")
+                        );
+                    }
+                }
+
+                mod and_root_section_has_repeated_keys {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        tag = "a"
+                        tag = "b"
+                        other = [1]
+                        tag = "c"
+                    "#;
+
+                    #[test]
+                    fn then_returns_last_value() {
+                        let mut p = Parser::new(RAW);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some("c"), actual["root"].get("tag").and_then(Value::as_str));
+                    }
+
+                    #[test]
+                    fn then_returns_array_with_repeated_keys_as_array() {
+                        let mut p = Parser::new(RAW).with_repeated_keys_as_array(true);
+
+                        let actual = p.read().unwrap();
+
+                        let root = &actual["root"];
+                        assert_eq!(
+                            Some(&Value::Array(vec![
+                                Value::new_string("a"),
+                                Value::new_string("b"),
+                                Value::new_string("c"),
+                            ])),
+                            root.get("tag")
+                        );
+                        assert_eq!(
+                            Some(&Value::Array(vec![Value::Integer(1)])),
+                            root.get("other")
+                        );
+                    }
+                }
+
+                mod and_root_section_has_dictionary_with_custom_separator {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        key: "value: with separator"
+                        dict: { inner: 1 }
+                    "#;
+
+                    #[test]
+                    fn then_returns_dictionary_with_keyval_separator() {
+                        let mut p = Parser::new(RAW).with_keyval_separator(':');
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        let mut dict = BTreeMap::new();
+                        dict.insert("inner".to_owned(), Value::Integer(1));
+                        section.dictionary.insert(
+                            "key".to_owned(),
+                            Value::String("value: with separator".to_owned()),
+                        );
+                        section
+                            .dictionary
+                            .insert("dict".to_owned(), Value::Dictionary(dict));
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_returns_error_naming_default_separator() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Expected '=' after a key", p.errors[0].desc);
+                    }
+                }
+
+                mod and_root_section_has_key_starting_with_digit {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        0key = "value"
+                    "#;
+
+                    #[test]
+                    fn then_returns_dictionary() {
+                        let mut p = Parser::new(RAW);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(
+                            Some(&Value::String("value".to_owned())),
+                            actual["root"].get("0key")
+                        );
+                    }
+
+                    #[test]
+                    fn then_returns_error_with_strict_keys() {
+                        let mut p = Parser::new(RAW).with_strict_keys(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Key must not start with a digit", p.errors[0].desc);
+                    }
+                }
+
+                mod and_root_section_has_percentages {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        discount = 15%
+                        tax = 2.5%
+                        after = 1
+                    "#;
+
+                    #[test]
+                    fn then_returns_floats_with_percent() {
+                        let mut p = Parser::new(RAW).with_percent(true);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        section
+                            .dictionary
+                            .insert("discount".to_owned(), Value::Float(0.15));
+                        section
+                            .dictionary
+                            .insert("tax".to_owned(), Value::Float(0.025));
+                        section
+                            .dictionary
+                            .insert("after".to_owned(), Value::Integer(1));
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_returns_error_without_percent() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(1, p.errors.len());
+                        assert_eq!("Unexpected '%' after a number", p.errors[0].desc);
+                        assert_eq!(RAW.find('%'), Some(p.errors[0].lo));
+                    }
+                }
+
+                mod and_root_section_has_ranges {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        nights = 3..7
+                        stay = 3..=7
+                        offset = -2..-1
+                    "#;
+
+                    #[test]
+                    fn then_returns_ranges_with_ranges() {
+                        let mut p = Parser::new(RAW).with_ranges(true);
+
+                        let actual = p.read().unwrap();
+
+                        let root = &actual["root"];
+                        assert_eq!(
+                            Some((3, 7, false)),
+                            root.get("nights").and_then(Value::as_range)
+                        );
+                        assert_eq!(
+                            Some((3, 7, true)),
+                            root.get("stay").and_then(Value::as_range)
+                        );
+                        assert_eq!(
+                            Some((-2, -1, false)),
+                            root.get("offset").and_then(Value::as_range)
+                        );
+
+                        let written = format!("[FOO]\n{root}");
+                        let mut p = Parser::new(&written).with_ranges(true);
+                        assert_eq!(Some(root), p.read().unwrap().get("FOO"));
+                    }
+
+                    #[test]
+                    fn then_returns_error_for_malformed_range() {
+                        for raw in [
+                            "nights = 3..",
+                            "nights = 3..=",
+                            "nights = 3.0..7.0",
+                            "nights = 3..7.5",
+                        ] {
+                            let mut p = Parser::new(raw).with_ranges(true);
+
+                            assert_eq!(None, p.read(), "{raw}");
+                            assert_eq!("Cannot parse a range", p.errors[0].desc);
+                        }
+                    }
+
+                    #[test]
+                    fn then_returns_error_without_ranges() {
+                        for raw in [RAW, "nights = 3.0..7.0\nother = 1"] {
+                            let mut p = Parser::new(raw);
+
+                            assert_eq!(None, p.read(), "{raw}");
+                            assert_eq!("Unexpected '..' after a number", p.errors[0].desc);
+                        }
+                    }
+                }
+
+                mod and_root_section_has_custom_comment_prefixes {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        ; semicolon comment
+                        key = "value"
+                        REM remark
+                        | cell | ; in table
+                    "#;
+
+                    #[test]
+                    fn then_returns_comments() {
+                        let p = Parser::new(RAW).with_comment_prefixes(&[";", "REM "]);
+
+                        let comments: Vec<_> = p
+                            .filter_map(|el| match el {
+                                Comment(text) => Some(text),
+                                _ => None,
+                            })
+                            .collect();
+
+                        // Comments trailing a row are consumed by the row.
+                        assert_eq!(vec![" semicolon comment\n", "remark\n"], comments);
+                    }
+
+                    #[test]
+                    fn then_returns_dictionary_and_rows() {
+                        let mut p = Parser::new(RAW).with_comment_prefixes(&[";", "REM "]);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        section
+                            .dictionary
+                            .insert("key".to_owned(), Value::String("value".to_owned()));
+                        section.rows.push(vec![Value::String("cell".to_owned())]);
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_root_section_has_heredoc_cell {
+                    use super::*;
+
+                    const RAW: &str = "
+                        | 1 | <<END
+                        first line
+                          | second line |
+                        END | three |
+                        | 2 | single | three |
+                    ";
+
+                    #[test]
+                    fn then_returns_multi_line_cell_with_heredocs() {
+                        let mut p = Parser::new(RAW).with_heredocs(true);
+
+                        let actual = p.read().unwrap();
+
+                        let rows = &actual["root"].rows;
+                        assert_eq!(2, rows.len());
+                        assert_eq!(
+                            vec![
+                                Value::String("1".to_owned()),
+                                Value::String(format!(
+                                    "{0}first line\n{0}  | second line |",
+                                    " ".repeat(24)
+                                )),
+                                Value::String("three".to_owned()),
+                            ],
+                            rows[0]
+                        );
+                        assert_eq!(3, rows[1].len());
+                    }
+
+                    #[test]
+                    fn then_returns_error_on_unterminated_heredoc() {
+                        let mut p = Parser::new("| 1 | <<END\nfirst line\n").with_heredocs(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Unterminated heredoc", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_error_without_heredocs() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                    }
+                }
+
+                mod and_root_section_has_control_characters {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_error_for_nul_in_key() {
+                        let mut p = Parser::new("ke\0y = 1");
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Unexpected control character U+0000", p.errors[0].desc);
+                        assert_eq!((2, 3), (p.errors[0].lo, p.errors[0].hi));
+                    }
+
+                    #[test]
+                    fn then_returns_error_for_control_character_in_cell() {
+                        let mut p = Parser::new("| a\x0Bb | c |");
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Unexpected control character U+000B", p.errors[0].desc);
+                        assert_eq!((3, 4), (p.errors[0].lo, p.errors[0].hi));
+                    }
+
+                    #[test]
+                    fn then_returns_error_for_control_character_in_section_name() {
+                        let mut p = Parser::new("[FO\x0CO]\nkey = 1");
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Unexpected control character U+000C", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_accepts_control_character_in_quoted_string() {
+                        let mut p = Parser::new("key = \"a\x0Bb\"\n| a\tb |");
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some("a\x0Bb"), actual["root"].get("key").unwrap().as_str());
+                    }
+                }
+
+                mod and_root_section_has_anchors {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        base = &room { view = "SV", beds = 2 }
+                        copy = *room
+                        list = [*room]
+                    "#;
+
+                    #[test]
+                    fn then_returns_expanded_references_with_anchors() {
+                        let mut p = Parser::new(RAW).with_anchors(true);
+
+                        let actual = p.read().unwrap();
+
+                        let mut room = BTreeMap::new();
+                        room.insert("view".to_owned(), Value::String("SV".to_owned()));
+                        room.insert("beds".to_owned(), Value::Integer(2));
+                        let room = Value::Dictionary(room);
+                        let mut expected = BTreeMap::new();
+                        let mut section = Section::new();
+                        section.dictionary.insert("base".to_owned(), room.clone());
+                        section.dictionary.insert("copy".to_owned(), room.clone());
+                        section
+                            .dictionary
+                            .insert("list".to_owned(), Value::Array(vec![room]));
+                        expected.insert("root".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_returns_error_on_unknown_anchor() {
+                        let mut p = Parser::new("copy = *missing").with_anchors(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Unknown anchor 'missing'", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_error_on_exponential_expansion() {
+                        let mut raw = "l0 = &l0 [1,1,1,1,1,1,1,1]\n".to_owned();
+                        for n in 1..8 {
+                            let reference = format!("*l{}", n - 1);
+                            let elements = [reference.as_str(); 8].join(",");
+                            raw += &format!("l{n} = &l{n} [{elements}]\n");
+                        }
+
+                        let mut p = Parser::new(&raw).with_anchors(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Anchor expansion too large", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_error_without_anchors() {
+                        let mut p = Parser::new(RAW);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot read a value", p.errors[0].desc);
+                    }
+                }
+
+                mod and_input_has_several_errors {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                    [FOO]
+                    a = 1
+                    b = @
+                    c = 3
+
+                    [BAR]
+                    d = @
+                    e = 5
+
+                    [BAZ]
+                    f = 6
+                    "#;
+
+                    fn read(action: ErrorAction) -> (Option<BTreeMap<String, Section>>, usize) {
+                        let mut errors = 0;
+                        let actual = Parser::new(RAW)
+                            .with_on_error(|_| {
+                                errors += 1;
+                                action
+                            })
+                            .read();
+                        (actual, errors)
+                    }
+
+                    #[test]
+                    fn then_returns_error_when_aborting() {
+                        assert_eq!((None, 1), read(ErrorAction::Abort));
+
+                        let mut p = Parser::new(RAW);
+                        assert_eq!(None, p.read());
+                        assert_eq!(1, p.errors.len());
+                    }
+
+                    #[test]
+                    fn then_skips_lines_with_errors() {
+                        let (actual, errors) = read(ErrorAction::SkipLine);
+                        let actual = actual.unwrap();
+
+                        assert_eq!(2, errors);
+                        assert_eq!(
+                            vec!["a", "c"],
+                            actual["FOO"].dictionary.keys().collect::<Vec<_>>()
+                        );
+                        assert_eq!(
+                            vec!["e"],
+                            actual["BAR"].dictionary.keys().collect::<Vec<_>>()
+                        );
+                        assert_eq!(Some(&Value::Integer(6)), actual["BAZ"].get("f"));
+                    }
+
+                    #[test]
+                    fn then_keeps_skipped_errors() {
+                        let mut p = Parser::new(RAW).with_on_error(|_| ErrorAction::SkipLine);
+
+                        assert!(p.read().is_some());
+                        assert_eq!(2, p.errors.len());
+                        assert_eq!("Cannot read a value", p.errors[1].desc);
+
+                        let mut skip = true;
+                        let mut p =
+                            Parser::new(RAW).with_on_error(|_| match core::mem::take(&mut skip) {
+                                true => ErrorAction::SkipLine,
+                                false => ErrorAction::Abort,
+                            });
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(2, p.errors.len());
+                    }
+
+                    #[test]
+                    fn then_parser_is_send() {
+                        fn assert_send<T: Send>(_: &T) {}
+
+                        assert_send(&Parser::new(RAW).with_on_error(|_| ErrorAction::SkipLine));
+                    }
+
+                    #[test]
+                    fn then_skips_sections_with_errors() {
+                        let (actual, errors) = read(ErrorAction::SkipSection);
+                        let actual = actual.unwrap();
+
+                        assert_eq!(2, errors);
+                        assert_eq!(
+                            vec!["a"],
+                            actual["FOO"].dictionary.keys().collect::<Vec<_>>()
+                        );
+                        assert!(actual["BAR"].dictionary.is_empty());
+                        assert_eq!(Some(&Value::Integer(6)), actual["BAZ"].get("f"));
+                    }
+                }
+
+                mod and_input_is_larger_than_max_input_size {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_error_beyond_max_input_size() {
+                        let raw = "key = [[[1]]]";
+
+                        let mut p = Parser::new(raw).with_max_input_size(raw.len() - 1);
+                        assert_eq!(None, p.read());
+                        assert_eq!("Input exceeds the maximum size", p.errors[0].desc);
+
+                        let mut p = Parser::new(raw).with_max_input_size(raw.len());
+                        assert!(p.read().is_some());
+                    }
+
+                    #[test]
+                    fn then_yields_no_elements_beyond_max_input_size() {
+                        let mut p = Parser::new("[FOO]\nkey = 1\n").with_max_input_size(3);
+
+                        assert_eq!(None, p.next());
+                        assert_eq!(None, p.next());
+                        assert_eq!(1, p.errors.len());
+                        assert_eq!("Input exceeds the maximum size", p.errors[0].desc);
+                    }
+                }
+
+                mod and_root_section_has_deeply_nested_array {
                     use super::*;
 
                     #[test]
-                    fn then_returns_dictionary() {
-                        let raw = r#"
-                            arr = ["WAW", "WRO"]
-                        "#;
-                        let mut p = Parser::new(raw);
+                    fn then_returns_error_beyond_max_depth() {
+                        let raw = format!("key = {}{}", "[".repeat(100_000), "]".repeat(100_000));
+                        let mut p = Parser::new(&raw);
 
-                        let actual = p.read().unwrap();
+                        assert_eq!(None, p.read());
+                        assert_eq!("Maximum nesting depth exceeded", p.errors[0].desc);
+                    }
 
-                        let mut expected = BTreeMap::new();
-                        let mut section = Section::new();
-                        let array = vec![
-                            Value::String("WAW".to_owned()),
-                            Value::String("WRO".to_owned()),
-                        ];
-                        section
-                            .dictionary
-                            .insert("arr".to_owned(), Value::Array(array));
-                        expected.insert("root".to_owned(), section);
-                        assert_eq!(expected, actual);
+                    #[test]
+                    fn then_returns_array_within_max_depth() {
+                        let mut p = Parser::new("key = [[[1]]]").with_max_depth(3);
+                        assert!(p.read().is_some());
+
+                        let mut p = Parser::new("key = [[[1]]]").with_max_depth(2);
+                        assert_eq!(None, p.read());
+                    }
+
+                    #[test]
+                    fn then_returns_array_without_growing_call_stack() {
+                        let raw = format!("key = {}{}", "[".repeat(10_000), "]".repeat(10_000));
+                        let mut p = Parser::new(&raw).with_max_depth(usize::MAX);
+
+                        assert!(p.read().is_some());
                     }
                 }
 
-                mod and_root_section_has_dictionary_with_dictionary {
+                mod and_root_section_has_custom_delimiters {
                     use super::*;
 
+                    const RAW: &str = r#"
+                        loc = <"M", "B">
+                        dist = (beach_km = 4.1, nested = <1>)
+                    "#;
+
                     #[test]
-                    fn then_returns_dictionary() {
-                        let raw = r#"
-                            ndict = { foo = "bar" }
-                        "#;
-                        let mut p = Parser::new(raw);
+                    fn then_returns_array_and_dictionary() {
+                        let mut p = Parser::new(RAW)
+                            .with_array_delimiters('<', '>')
+                            .with_dictionary_delimiters('(', ')');
 
                         let actual = p.read().unwrap();
 
                         let mut expected = BTreeMap::new();
                         let mut section = Section::new();
+                        let array =
+                            vec![Value::String("M".to_owned()), Value::String("B".to_owned())];
+                        section
+                            .dictionary
+                            .insert("loc".to_owned(), Value::Array(array));
                         let mut dict = BTreeMap::new();
-                        dict.insert("foo".to_owned(), Value::String("bar".to_owned()));
+                        dict.insert("beach_km".to_owned(), Value::Float(4.1));
+                        dict.insert("nested".to_owned(), Value::Array(vec![Value::Integer(1)]));
                         section
                             .dictionary
-                            .insert("ndict".to_owned(), Value::Dictionary(dict));
+                            .insert("dist".to_owned(), Value::Dictionary(dict));
                         expected.insert("root".to_owned(), section);
                         assert_eq!(expected, actual);
                     }
-                }
-
-                mod and_root_section_has_dictionary_with_dictionary_with_new_lines {
-                    use super::*;
 
                     #[test]
-                    fn then_returns_dictionary() {
-                        let raw = r#"
-                            R75042 = {
-                            view = "SV"
-                            loc  = ["M", "B"]
-                            dist = { beach_km = 4.1 }
-                        }"#;
-                        let mut p = Parser::new(raw);
+                    fn then_returns_error_with_default_delimiters() {
+                        let mut p = Parser::new(RAW);
 
-                        let actual = p.read().unwrap();
-
-                        let mut expected = BTreeMap::new();
-                        let mut sect = Section::new();
-                        let mut dict = BTreeMap::new();
-                        dict.insert("view".to_owned(), Value::String("SV".to_owned()));
-                        let array =
-                            vec![Value::String("M".to_owned()), Value::String("B".to_owned())];
-                        dict.insert("loc".to_owned(), Value::Array(array));
-                        let mut dict_dict = BTreeMap::new();
-                        dict_dict.insert("beach_km".to_owned(), Value::Float(4.1));
-                        dict.insert("dist".to_owned(), Value::Dictionary(dict_dict));
-                        sect.dictionary
-                            .insert("R75042".to_owned(), Value::Dictionary(dict));
-                        expected.insert("root".to_owned(), sect);
-                        assert_eq!(expected, actual);
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot read a value", p.errors[0].desc);
                     }
                 }
 
-                mod and_root_section_has_dictionary_with_dictionary_with_no_value {
+                mod and_root_section_has_content_before_first_section {
                     use super::*;
 
+                    const RAW: &str = r#"
+                        # comments are fine
+                        key = 1
+                        [FOO]
+                        other = 2
+                    "#;
+
                     #[test]
-                    fn then_returns_error() {
-                        let raw = r#"
-                            key =
-                        "#;
-                        let mut p = Parser::new(raw);
+                    fn then_returns_sections() {
+                        let actual = Parser::new(RAW).read().unwrap();
 
-                        let actual = p.read();
+                        assert_eq!(
+                            Some(&Value::Integer(2)),
+                            actual.get("FOO").and_then(|foo| foo.get("other"))
+                        );
+                    }
 
-                        assert_eq!(None, actual);
+                    #[test]
+                    fn then_returns_error_with_require_sections() {
+                        let mut p = Parser::new(RAW).with_require_sections(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Content outside of any section", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_sections_with_require_sections() {
+                        let raw = "# comment\n[FOO]\nkey = 1\n| row |\n";
+                        let mut p = Parser::new(raw).with_require_sections(true);
+
+                        assert!(p.read().is_some());
                     }
                 }
 
@@ -1019,6 +3410,346 @@ mod tests {
                     }
                 }
 
+                mod and_spans_are_enabled {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_sections_with_spans() {
+                        let raw = "[FIRST]\nkey = 1\n[SECOND]\n| a | b |\n\n[THIRD]\nkey = 3\n";
+                        let mut p = Parser::new(raw).with_spans(true);
+
+                        let actual = p.read().unwrap();
+
+                        let start = raw.find("[SECOND]").unwrap();
+                        let end = raw.find("[THIRD]").unwrap();
+                        assert_eq!(Some((start, end)), actual["SECOND"].span);
+                        assert_eq!("[SECOND]\n| a | b |\n\n", &raw[start..end]);
+                        assert_eq!(Some((0, start)), actual["FIRST"].span);
+                        assert_eq!(Some((end, raw.len())), actual["THIRD"].span);
+                    }
+
+                    #[test]
+                    fn then_spans_are_not_recorded_by_default() {
+                        let mut p = Parser::new("[FIRST]\nkey = 1\n");
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(None, actual["FIRST"].span);
+                    }
+                }
+
+                mod and_section_header_ends_input_without_newline {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_empty_section() {
+                        let mut p = Parser::new("[FOO]");
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        expected.insert("FOO".to_owned(), Section::new());
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_returns_all_sections() {
+                        let mut p = Parser::new("[FOO]\n[BAR]");
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        expected.insert("FOO".to_owned(), Section::new());
+                        expected.insert("BAR".to_owned(), Section::new());
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_section_has_attributes {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_section_with_attributes() {
+                        let raw = r#"
+                            [FOO](type=table, version=2, label = "Foo table")
+                            | 1 | 2 |
+                            [BAR]
+                            key = 1
+                        "#;
+                        let mut p = Parser::new(raw);
+
+                        let actual = p.read().unwrap();
+
+                        let foo = &actual["FOO"];
+                        assert_eq!(3, foo.attributes.len());
+                        assert_eq!(
+                            Some(&Value::String("table".to_owned())),
+                            foo.attribute("type")
+                        );
+                        assert_eq!(Some(&Value::Integer(2)), foo.attribute("version"));
+                        assert_eq!(
+                            Some(&Value::String("Foo table".to_owned())),
+                            foo.attribute("label")
+                        );
+                        assert_eq!(1, foo.rows.len());
+
+                        let bar = &actual["BAR"];
+                        assert!(bar.attributes.is_empty());
+                        assert_eq!(None, bar.attribute("type"));
+                    }
+
+                    #[test]
+                    fn then_returns_error_on_unterminated_attributes() {
+                        let mut p = Parser::new("[FOO](type=table");
+
+                        assert_eq!(None, p.read());
+                        assert_eq!("Cannot finish section attributes", p.errors[0].desc);
+                    }
+                }
+
+                mod and_indentation_mixes_tabs_and_spaces {
+                    use super::*;
+                    use crate::Warning;
+
+                    const RAW: &str = "[FOO]\n \tkey = 1\n\tother = 2\n  | a |\n";
+
+                    #[test]
+                    fn then_returns_warning_with_indent_lint() {
+                        let mut p = Parser::new(RAW).with_indent_lint(true);
+
+                        let (actual, warnings) = p.read_with_warnings().unwrap();
+
+                        assert_eq!(2, actual["FOO"].dictionary.len());
+                        assert_eq!(
+                            vec![Warning {
+                                lo: 6,
+                                hi: 8,
+                                desc: "Indentation mixes tabs and spaces".to_owned(),
+                            }],
+                            warnings
+                        );
+                    }
+
+                    #[test]
+                    fn then_returns_no_warning_by_default() {
+                        let mut p = Parser::new(RAW);
+
+                        let (_, warnings) = p.read_with_warnings().unwrap();
+
+                        assert!(warnings.is_empty());
+                    }
+                }
+
+                mod and_section_has_end_marker {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        [FOO]
+                        key = 1
+                        [/FOO]
+                        root_key = 2
+                        [BAR]
+                        | a |
+                        []
+                        | root |
+                    "#;
+
+                    #[test]
+                    fn then_returns_content_after_marker_in_root() {
+                        let mut p = Parser::new(RAW).with_section_end_markers(true);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut foo = Section::new();
+                        foo.dictionary.insert("key".to_owned(), Value::Integer(1));
+                        expected.insert("FOO".to_owned(), foo);
+                        let mut bar = Section::new();
+                        bar.rows.push(vec![Value::String("a".to_owned())]);
+                        expected.insert("BAR".to_owned(), bar);
+                        let mut root = Section::new();
+                        root.dictionary
+                            .insert("root_key".to_owned(), Value::Integer(2));
+                        root.rows.push(vec![Value::String("root".to_owned())]);
+                        expected.insert("root".to_owned(), root);
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_returns_error_on_mismatched_marker() {
+                        let mut p = Parser::new("[FOO]\n[/BAR]\n").with_section_end_markers(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(
+                            "Cannot close section 'BAR' while 'FOO' is open",
+                            p.errors[0].desc
+                        );
+
+                        let mut p = Parser::new("[/BAR]\n").with_section_end_markers(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(
+                            "Cannot close section 'BAR' while none is open",
+                            p.errors[0].desc
+                        );
+
+                        let mut p = Parser::new("[]\n").with_section_end_markers(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(
+                            "Cannot close a section while none is open",
+                            p.errors[0].desc
+                        );
+                    }
+
+                    #[test]
+                    fn then_returns_markers_as_sections_by_default() {
+                        let mut p = Parser::new(RAW);
+
+                        let actual = p.read().unwrap();
+
+                        assert!(actual.contains_key("/FOO"));
+                        assert!(!actual.contains_key(""));
+                        assert_eq!(1, actual["root"].rows.len());
+                    }
+                }
+
+                mod and_section_header_has_content_on_same_line {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_content_in_that_section() {
+                        let raw = "[FOO] key = 1 | a | b |\n[BAR](x = 1) other = 2\n";
+
+                        let actual = Parser::new(raw).read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut foo = Section::new();
+                        foo.dictionary.insert("key".to_owned(), Value::Integer(1));
+                        foo.rows.push(vec![
+                            Value::String("a".to_owned()),
+                            Value::String("b".to_owned()),
+                        ]);
+                        expected.insert("FOO".to_owned(), foo);
+                        let mut bar = Section::new();
+                        bar.attributes.insert("x".to_owned(), Value::Integer(1));
+                        bar.dictionary.insert("other".to_owned(), Value::Integer(2));
+                        expected.insert("BAR".to_owned(), bar);
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_ends_bareword_at_row_with_inline_sections() {
+                        let raw = "[FOO] mode = read-only | a | b |\nother = x | y\n";
+
+                        let mut p = Parser::new(raw)
+                            .with_bareword_values(true)
+                            .with_inline_sections(true);
+                        let foo = p.read().unwrap().remove("FOO").unwrap();
+
+                        assert_eq!(Some("read-only"), foo.get("mode").and_then(Value::as_str));
+                        assert_eq!(Some("x | y"), foo.get("other").and_then(Value::as_str));
+                        assert_eq!(
+                            vec![vec![Value::new_string("a"), Value::new_string("b")]],
+                            foo.rows
+                        );
+
+                        let mut p = Parser::new(raw).with_bareword_values(true);
+                        let foo = p.read().unwrap().remove("FOO").unwrap();
+
+                        assert_eq!(
+                            Some("read-only | a | b |"),
+                            foo.get("mode").and_then(Value::as_str)
+                        );
+                        assert!(foo.rows.is_empty());
+                    }
+                }
+
+                mod and_section_header_has_trailing_comment {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        [FOO] # this is foo, not [BAR]
+                        key = 1
+                        [BAR] # this is bar
+                        | a |
+                    "#;
+
+                    #[test]
+                    fn then_returns_the_comment() {
+                        let actual = Parser::new(RAW).collect::<Vec<_>>();
+
+                        assert_eq!(
+                            vec![
+                                Element::Section("FOO".to_owned()),
+                                Comment(" this is foo, not [BAR]\n".to_owned()),
+                                Element::Entry("key".to_owned(), Value::Integer(1)),
+                                Element::Section("BAR".to_owned()),
+                                Comment(" this is bar\n".to_owned()),
+                                Element::Row(vec![Value::String("a".to_owned())]),
+                            ],
+                            actual
+                        );
+                    }
+
+                    #[test]
+                    fn then_skips_the_comment_of_filtered_section() {
+                        let mut p = Parser::new_filtered(RAW, vec!["BAR"]);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut bar = Section::new();
+                        bar.rows.push(vec![Value::String("a".to_owned())]);
+                        expected.insert("BAR".to_owned(), bar);
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_section_name_is_empty {
+                    use super::*;
+
+                    const RAW: &str = r#"
+                        [FOO]
+                        key = 1
+                        []
+                        key = 2
+                        | cell |
+                    "#;
+
+                    #[test]
+                    fn then_returns_root_section() {
+                        let mut p = Parser::new(RAW);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(2, actual.len());
+                        let root = &actual[Section::DEFAULT_NAME];
+                        assert_eq!(Some(&Value::Integer(2)), root.get("key"));
+                        assert_eq!(1, root.rows.len());
+                        assert_eq!(Some(&Value::Integer(1)), actual["FOO"].get("key"));
+                    }
+
+                    #[test]
+                    fn then_returns_error_with_require_sections() {
+                        let mut p = Parser::new(RAW).with_require_sections(true);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(1, p.errors.len());
+                        assert_eq!("Content outside of any section", p.errors[0].desc);
+                    }
+
+                    #[test]
+                    fn then_returns_root_section_when_filtered() {
+                        let mut p = Parser::new_filtered(RAW, vec!["root"]);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(vec!["root"], actual.keys().collect::<Vec<_>>());
+                        assert_eq!(Some(&Value::Integer(2)), actual["root"].get("key"));
+                    }
+                }
+
                 mod and_section_is_duplicated {
                     use super::*;
 
@@ -1076,6 +3807,61 @@ mod tests {
                     }
                 }
 
+                mod and_then_accepted_section_with_root_in_filter {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_root_and_accepted_section() {
+                        let raw = r#"
+                            nkey = "nvalue"
+                            | ncol1 | ncol2 |
+                            [FILTERED]
+                            fkey = "fvalue"
+                            [ACCEPTED]
+                            key = "value"
+                        "#;
+                        let mut p =
+                            Parser::new_filtered(raw, vec!["ACCEPTED"]).with_root_in_filter(true);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        let mut root = Section::new();
+                        root.dictionary
+                            .insert("nkey".to_owned(), Value::String("nvalue".to_owned()));
+                        root.rows.push(vec![
+                            Value::String("ncol1".to_string()),
+                            Value::String("ncol2".to_string()),
+                        ]);
+                        expected.insert("root".to_owned(), root);
+                        let mut section = Section::new();
+                        section
+                            .dictionary
+                            .insert("key".to_owned(), Value::String("value".to_owned()));
+                        expected.insert("ACCEPTED".to_owned(), section);
+                        assert_eq!(expected, actual);
+                    }
+
+                    #[test]
+                    fn then_returns_root_only_when_nothing_accepted() {
+                        let raw = r#"
+                            nkey = "nvalue"
+                            [FILTERED]
+                            fkey = "fvalue"
+                        "#;
+                        let mut p =
+                            Parser::new_filtered(raw, vec!["ACCEPTED"]).with_root_in_filter(true);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(1, actual.len());
+                        assert_eq!(
+                            Some(&Value::String("nvalue".to_owned())),
+                            actual["root"].get("nkey")
+                        );
+                    }
+                }
+
                 mod and_then_accepted_section {
                     use super::*;
 
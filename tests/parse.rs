@@ -29,22 +29,70 @@ fn hotel_ion() {
     assert_eq!(expected, ion.to_string());
 }
 
+#[cfg(feature = "binary")]
+#[test]
+fn hotel_ion_bytes() {
+    let ion = read_ion("tests/data/hotel.ion");
+    let bytes = ion.to_bytes();
+
+    assert_eq!(ion, Ion::from_bytes(&bytes).unwrap());
+}
+
 #[test]
 fn broken_array_and_eof() {
     let ion_err = read_err_ion("tests/data/broken_array_and_eof.ion");
 
-    let expected =
-        "ParserErrors([ParserError { lo: 55, hi: 55, desc: \"Cannot finish an array\" }])";
+    let expected = "ParserErrors([ParserError { lo: 55, hi: 55, desc: \"Cannot finish an array\", kind: UnterminatedArray, section: Some(\"CONTRACT\"), line: 3, column: 17 }])";
 
     assert_eq!(expected, ion_err.to_string());
 }
 
+#[cfg(feature = "serde")]
+#[test]
+fn broken_array_and_eof_diagnostic_json() {
+    let input = fs::read_to_string("tests/data/broken_array_and_eof.ion").unwrap();
+    let ion_err = input.parse::<Ion>().unwrap_err();
+
+    let expected = r#"{"column":17,"kind":"UnterminatedArray","line":3,"message":"Cannot finish an array","section":"CONTRACT"}"#;
+
+    assert_eq!(expected, ion_err.to_diagnostic_json());
+}
+
 #[test]
 fn broken_dictionary_and_eof() {
     let ion_err = read_err_ion("tests/data/broken_dictionary_and_eof.ion");
 
-    let expected =
-        "ParserErrors([ParserError { lo: 67, hi: 67, desc: \"Cannot finish a dictionary\" }])";
+    let expected = "ParserErrors([ParserError { lo: 67, hi: 67, desc: \"Cannot finish a dictionary\", kind: UnterminatedDictionary, section: Some(\"CONTRACT\"), line: 3, column: 24 }])";
 
     assert_eq!(expected, ion_err.to_string());
 }
+
+#[test]
+fn parser_accumulates_errors() {
+    let mut parser = ion::Parser::new("[FOO]\n| a\u{0}b | c\u{b}d |\n| ok |\n");
+
+    assert_eq!(None, parser.read());
+
+    let errors: Vec<_> = parser
+        .errors
+        .iter()
+        .map(|e| (e.lo, e.hi, e.desc.as_str()))
+        .collect();
+    assert_eq!(
+        vec![
+            (9, 10, "Unexpected control character U+0000"),
+            (15, 16, "Unexpected control character U+000B"),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn ion_returns_accumulated_errors() {
+    let err = "[FOO]\n| a\u{0}b | c\u{b}d |\n".parse::<Ion>().unwrap_err();
+
+    match err {
+        ion::IonError::ParserErrors(errors) => assert_eq!(2, errors.len()),
+        _ => panic!("expected IonError::ParserErrors, got {err:?}"),
+    }
+}